@@ -1,4 +1,4 @@
-use binja::{BinarySerialize, BinarySerializer, error::Result, to_bytes};
+use binja::{BinarySerialize, BinarySerializer, Sink, error::Result, to_bytes};
 use criterion::{Criterion, criterion_group, criterion_main};
 
 struct TestStruct {
@@ -25,7 +25,7 @@ impl Default for TestStruct {
 }
 
 impl BinarySerialize for TestStruct {
-    fn binary_serialize(&self, serializer: &mut BinarySerializer) -> Result<()> {
+    fn binary_serialize<S: Sink>(&self, serializer: &mut BinarySerializer<S>) -> Result<()> {
         self.a.binary_serialize(serializer)?;
         self.b.binary_serialize(serializer)?;
         self.c.binary_serialize(serializer)?;
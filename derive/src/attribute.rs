@@ -1,4 +1,6 @@
-use darling::{FromDeriveInput, FromField, FromVariant};
+use darling::{FromDeriveInput, FromField, FromMeta, FromVariant};
+use proc_macro2::TokenStream;
+use quote::quote;
 
 use crate::bit_field::{VALID_BIT_FIELD_TYPES, is_valid_bit_field_type};
 
@@ -7,6 +9,68 @@ use crate::bit_field::{VALID_BIT_FIELD_TYPES, is_valid_bit_field_type};
 pub struct StructAttributes {
     pub ident: syn::Ident,
     pub generics: syn::Generics,
+
+    // #[binja(magic = b"PK\x03\x04")]
+    pub magic: Option<syn::LitByteStr>,
+
+    // #[binja(pad_to = 64, fill = 0xff, check_padding)]
+    pub pad_to: Option<usize>,
+    pub fill: Option<u8>,
+    pub check_padding: Option<()>,
+
+    // #[binja(big_endian)] / #[binja(little_endian)]
+    pub big_endian: Option<()>,
+    pub little_endian: Option<()>,
+}
+
+impl StructAttributes {
+    pub fn padding(&self) -> PaddingOptions {
+        PaddingOptions {
+            fill: self.fill.unwrap_or(0),
+            check: self.check_padding.is_some(),
+        }
+    }
+
+    pub fn endianness(&self) -> syn::Result<Option<TokenStream>> {
+        endianness_builder(&self.ident, self.big_endian, self.little_endian)
+    }
+}
+
+/// Returns the `Config` builder method applying a `big_endian` or `little_endian`
+/// attribute, if any.
+fn endianness_builder(
+    span: impl quote::ToTokens,
+    big_endian: Option<()>,
+    little_endian: Option<()>,
+) -> syn::Result<Option<TokenStream>> {
+    match (big_endian, little_endian) {
+        (Some(()), Some(())) => Err(syn::Error::new_spanned(
+            span,
+            "Cannot be both `big_endian` and `little_endian`",
+        )),
+        (Some(()), None) => Ok(Some(quote! { with_big_endian })),
+        (None, Some(())) => Ok(Some(quote! { with_little_endian })),
+        (None, None) => Ok(None),
+    }
+}
+
+/// How the padding of a struct is written, and whether it is checked when parsing.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PaddingOptions {
+    pub fill: u8,
+    pub check: bool,
+}
+
+impl PaddingOptions {
+    /// Returns the fill byte parsed padding must hold, as an `Option<u8>` expression.
+    pub fn check_fill(&self) -> TokenStream {
+        let fill = self.fill;
+        if self.check {
+            quote! { ::core::option::Option::Some(#fill) }
+        } else {
+            quote! { ::core::option::Option::None }
+        }
+    }
 }
 
 #[derive(Debug, FromDeriveInput)]
@@ -18,6 +82,10 @@ pub struct EnumAttributes {
     // #[binja(repr = u32, untagged)]
     pub repr: Option<String>,
     pub untagged: Option<()>,
+
+    // #[binja(big_endian)] / #[binja(little_endian)]
+    pub big_endian: Option<()>,
+    pub little_endian: Option<()>,
     // You can extend with more options as needed
 }
 
@@ -47,6 +115,10 @@ impl EnumAttributes {
     pub fn untagged(&self) -> bool {
         self.untagged.is_some()
     }
+
+    pub fn endianness(&self) -> syn::Result<Option<TokenStream>> {
+        endianness_builder(&self.ident, self.big_endian, self.little_endian)
+    }
 }
 
 #[derive(Debug, FromField)]
@@ -57,17 +129,111 @@ pub struct FieldAttributes {
 
     pub ty: syn::Type,
 
-    // #[binja(skip)]
+    // #[binja(skip)] / #[binja(skip, default = "Kind::File")]
     pub skip: Option<()>,
+    pub default: Option<syn::Expr>,
+
+    // #[binja(calc = "u32::from(*width) * u32::from(*height)")]
+    pub calc: Option<syn::Expr>,
 
     pub bits: Option<u8>,
 
     pub no_overflow: Option<()>,
+
+    // #[binja(varint)] / #[binja(fixed)]
+    pub varint: Option<()>,
+    pub fixed: Option<()>,
+
+    // #[binja(size = "len")] / #[binja(size = "len", exact)]
+    pub size: Option<syn::Expr>,
+    pub exact: Option<()>,
+
+    // #[binja(len_of = "body")]
+    pub len_of: Option<syn::Ident>,
+
+    // #[binja(count = "num_items")]
+    pub count: Option<syn::Expr>,
+
+    // #[binja(if = "flags & 0x4 != 0")], see `FieldAttributes::parse`
+    pub condition: Option<syn::Expr>,
+
+    // #[binja(const = 0x01)], see `FieldAttributes::parse`
+    pub constant: Option<syn::Expr>,
+
+    // #[binja(pad_before = 2, pad_after = 2, align = 4)]
+    pub pad_before: Option<usize>,
+    pub pad_after: Option<usize>,
+    pub align: Option<usize>,
+
+    // #[binja(big_endian)] / #[binja(little_endian)]
+    pub big_endian: Option<()>,
+    pub little_endian: Option<()>,
+
+    // #[binja(with = "module")] / #[binja(serialize_with = "fn", parse_with = "fn")]
+    pub with: Option<syn::Path>,
+    pub serialize_with: Option<syn::Path>,
+    pub parse_with: Option<syn::Path>,
+
+    // #[binja(map = "Kind::from", into = "u8")]
+    // #[binja(try_map = "Kind::try_from", into = "u8")]
+    pub map: Option<syn::Expr>,
+    pub try_map: Option<syn::Expr>,
+    pub into: Option<syn::Type>,
+
+    // #[binja(assert = "*version < 3", message = "unsupported version")]
+    pub assert: Option<Assertion>,
+    pub message: Option<String>,
 }
 
 impl FieldAttributes {
+    /// Reads the `#[binja(...)]` attributes of a field.
+    ///
+    /// `if` and `const` are keywords that attribute paths can't hold, so they are read as
+    /// `condition` and `constant`.
+    pub fn parse(field: &syn::Field) -> darling::Result<Self> {
+        let mut field = field.clone();
+        for attr in &mut field.attrs {
+            if let syn::Meta::List(list) = &mut attr.meta
+                && list.path.is_ident("binja")
+            {
+                // only keys are renamed, i.e. the first token and those following a
+                // top-level comma, so `if` and `const` inside values are kept
+                let mut key = true;
+                list.tokens = list
+                    .tokens
+                    .clone()
+                    .into_iter()
+                    .map(|token| {
+                        let token = match token {
+                            proc_macro2::TokenTree::Ident(ident) if key && ident == "if" => {
+                                syn::Ident::new("condition", ident.span()).into()
+                            }
+                            proc_macro2::TokenTree::Ident(ident) if key && ident == "const" => {
+                                syn::Ident::new("constant", ident.span()).into()
+                            }
+                            token => token,
+                        };
+                        key = matches!(&token, proc_macro2::TokenTree::Punct(punct) if punct.as_char() == ',');
+                        token
+                    })
+                    .collect();
+            }
+        }
+
+        Self::from_field(&field)
+    }
+
+    /// Whether the field is left out of the wire, being skipped or computed.
     pub fn skip(&self) -> bool {
-        self.skip.is_some()
+        self.skip.is_some() || self.calc.is_some()
+    }
+
+    pub fn default(&self) -> Option<&syn::Expr> {
+        self.default.as_ref()
+    }
+
+    pub fn calc(&self) -> Option<&syn::Expr> {
+        self.calc.as_ref()
     }
 
     pub fn bits(&self) -> Option<u8> {
@@ -78,6 +244,136 @@ impl FieldAttributes {
         self.no_overflow.is_some()
     }
 
+    pub fn varint(&self) -> bool {
+        self.varint.is_some()
+    }
+
+    pub fn fixed(&self) -> bool {
+        self.fixed.is_some()
+    }
+
+    pub fn size(&self) -> Option<&syn::Expr> {
+        self.size.as_ref()
+    }
+
+    pub fn exact(&self) -> bool {
+        self.exact.is_some()
+    }
+
+    pub fn len_of(&self) -> Option<&syn::Ident> {
+        self.len_of.as_ref()
+    }
+
+    pub fn count(&self) -> Option<&syn::Expr> {
+        self.count.as_ref()
+    }
+
+    pub fn condition(&self) -> Option<&syn::Expr> {
+        self.condition.as_ref()
+    }
+
+    pub fn constant(&self) -> Option<&syn::Expr> {
+        self.constant.as_ref()
+    }
+
+    pub fn pad_before(&self) -> Option<usize> {
+        self.pad_before
+    }
+
+    pub fn pad_after(&self) -> Option<usize> {
+        self.pad_after
+    }
+
+    pub fn align(&self) -> Option<usize> {
+        self.align
+    }
+
+    /// Returns the `Config` builder method applying the field's `big_endian` or
+    /// `little_endian` attribute, if any.
+    pub fn endianness(&self) -> Option<TokenStream> {
+        if self.big_endian.is_some() {
+            Some(quote! { with_big_endian })
+        } else if self.little_endian.is_some() {
+            Some(quote! { with_little_endian })
+        } else {
+            None
+        }
+    }
+
+    /// Returns the `Config` builder methods applying the field's integer encoding and
+    /// endianness attributes.
+    ///
+    /// `len_of` fields always use the fixed encoding, as their size is reserved before
+    /// their value is known.
+    pub fn config_builders(&self) -> Vec<TokenStream> {
+        let mut builders = Vec::new();
+        if self.varint() {
+            builders.push(quote! { with_varint_encoding });
+        } else if self.fixed() || self.len_of.is_some() {
+            builders.push(quote! { with_fixed_int_encoding });
+        }
+        builders.extend(self.endianness());
+        builders
+    }
+
+    /// Returns the function serializing the field, as given by `serialize_with` or
+    /// `with`, if any.
+    pub fn serialize_with(&self) -> Option<TokenStream> {
+        match (&self.serialize_with, &self.with) {
+            (Some(function), _) => Some(quote! { #function }),
+            (None, Some(module)) => Some(quote! { #module::binary_serialize }),
+            (None, None) => None,
+        }
+    }
+
+    /// Returns the function parsing the field, as given by `parse_with` or `with`, if any.
+    pub fn parse_with(&self) -> Option<TokenStream> {
+        match (&self.parse_with, &self.with) {
+            (Some(function), _) => Some(quote! { #function }),
+            (None, Some(module)) => Some(quote! { #module::binary_parse }),
+            (None, None) => None,
+        }
+    }
+
+    pub fn has_custom_codec(&self) -> bool {
+        self.with.is_some() || self.serialize_with.is_some() || self.parse_with.is_some()
+    }
+
+    pub fn map(&self) -> Option<&syn::Expr> {
+        self.map.as_ref()
+    }
+
+    pub fn try_map(&self) -> Option<&syn::Expr> {
+        self.try_map.as_ref()
+    }
+
+    /// Whether the parsed value is converted by `map` or `try_map`.
+    pub fn has_mapping(&self) -> bool {
+        self.map.is_some() || self.try_map.is_some()
+    }
+
+    pub fn into_type(&self) -> Option<&syn::Type> {
+        self.into.as_ref()
+    }
+
+    pub fn assert(&self) -> Option<&syn::Expr> {
+        self.assert.as_ref().map(|assert| &assert.expr)
+    }
+
+    /// Returns the message of the error raised when the `assert` expression doesn't hold:
+    /// the `message` attribute, or the expression as written.
+    pub fn assert_message(&self) -> Option<String> {
+        let assert = self.assert.as_ref()?;
+        Some(match &self.message {
+            Some(message) => message.clone(),
+            None => format!("assertion failed: {}", assert.text),
+        })
+    }
+
+    pub fn has_padding(&self) -> bool {
+        self.pad_before.is_some() || self.pad_after.is_some() || self.align.is_some()
+    }
+
     pub fn validate(&self, span: proc_macro2::Span) -> syn::Result<()> {
         if self.skip.is_some() && self.bits.is_some() {
             return Err(syn::Error::new(
@@ -99,6 +395,211 @@ impl FieldAttributes {
             ));
         }
 
+        if self.varint.is_some() && self.fixed.is_some() {
+            return Err(syn::Error::new(
+                span,
+                "Field cannot be both `varint` and `fixed`",
+            ));
+        }
+
+        if (self.varint.is_some() || self.fixed.is_some())
+            && (self.skip.is_some() || self.bits.is_some())
+        {
+            return Err(syn::Error::new(
+                span,
+                "varint and fixed cannot be used with skip or bits",
+            ));
+        }
+
+        if self.size.is_some() && (self.skip.is_some() || self.bits.is_some()) {
+            return Err(syn::Error::new(
+                span,
+                "size cannot be used with skip or bits",
+            ));
+        }
+
+        if self.len_of.is_some()
+            && (self.skip.is_some()
+                || self.bits.is_some()
+                || self.varint.is_some()
+                || self.size.is_some())
+        {
+            return Err(syn::Error::new(
+                span,
+                "len_of cannot be used with skip, bits, varint or size",
+            ));
+        }
+
+        if self.count.is_some()
+            && (self.skip.is_some() || self.bits.is_some() || self.len_of.is_some())
+        {
+            return Err(syn::Error::new(
+                span,
+                "count cannot be used with skip, bits or len_of",
+            ));
+        }
+
+        if self.condition.is_some()
+            && (self.skip.is_some()
+                || self.bits.is_some()
+                || self.len_of.is_some()
+                || self.size.is_some()
+                || self.count.is_some())
+        {
+            return Err(syn::Error::new(
+                span,
+                "if cannot be used with skip, bits, len_of, size or count",
+            ));
+        }
+
+        if self.constant.is_some()
+            && (self.skip.is_some()
+                || self.bits.is_some()
+                || self.len_of.is_some()
+                || self.size.is_some()
+                || self.count.is_some()
+                || self.condition.is_some())
+        {
+            return Err(syn::Error::new(
+                span,
+                "const cannot be used with skip, bits, len_of, size, count or if",
+            ));
+        }
+
+        if self.big_endian.is_some() && self.little_endian.is_some() {
+            return Err(syn::Error::new(
+                span,
+                "Field cannot be both `big_endian` and `little_endian`",
+            ));
+        }
+
+        if (self.big_endian.is_some() || self.little_endian.is_some()) && self.bits.is_some() {
+            return Err(syn::Error::new(
+                span,
+                "big_endian and little_endian cannot be used with bits",
+            ));
+        }
+
+        if self.with.is_some() && (self.serialize_with.is_some() || self.parse_with.is_some()) {
+            return Err(syn::Error::new(
+                span,
+                "with cannot be used with serialize_with or parse_with",
+            ));
+        }
+
+        if self.has_custom_codec()
+            && (self.skip.is_some()
+                || self.bits.is_some()
+                || self.count.is_some()
+                || self.condition.is_some()
+                || self.constant.is_some())
+        {
+            return Err(syn::Error::new(
+                span,
+                "with, serialize_with and parse_with cannot be used with skip, bits, count, if or const",
+            ));
+        }
+
+        if self.default.is_some() && self.skip.is_none() {
+            return Err(syn::Error::new(
+                span,
+                "default can only be used with a skip attribute",
+            ));
+        }
+
+        if self.calc.is_some()
+            && (self.skip.is_some()
+                || self.bits.is_some()
+                || self.varint.is_some()
+                || self.fixed.is_some()
+                || self.size.is_some()
+                || self.len_of.is_some()
+                || self.count.is_some()
+                || self.condition.is_some()
+                || self.constant.is_some()
+                || self.big_endian.is_some()
+                || self.little_endian.is_some()
+                || self.has_custom_codec()
+                || self.map.is_some()
+                || self.try_map.is_some()
+                || self.into.is_some()
+                || self.assert.is_some())
+        {
+            return Err(syn::Error::new(
+                span,
+                "calc fields are never read nor written, so they can't have other attributes than padding",
+            ));
+        }
+
+        if self.exact.is_some() && self.size.is_none() {
+            return Err(syn::Error::new(
+                span,
+                "exact can only be used with a size attribute",
+            ));
+        }
+
+        if self.map.is_some() && self.try_map.is_some() {
+            return Err(syn::Error::new(
+                span,
+                "Field cannot have both `map` and `try_map`",
+            ));
+        }
+
+        if (self.map.is_some() || self.try_map.is_some())
+            && (self.skip.is_some()
+                || self.bits.is_some()
+                || self.len_of.is_some()
+                || self.count.is_some()
+                || self.constant.is_some()
+                || self.with.is_some()
+                || self.parse_with.is_some())
+        {
+            return Err(syn::Error::new(
+                span,
+                "map and try_map cannot be used with skip, bits, len_of, count, const, with or parse_with",
+            ));
+        }
+
+        if self.into.is_some()
+            && (self.skip.is_some()
+                || self.bits.is_some()
+                || self.len_of.is_some()
+                || self.count.is_some()
+                || self.constant.is_some()
+                || self.with.is_some()
+                || self.serialize_with.is_some())
+        {
+            return Err(syn::Error::new(
+                span,
+                "into cannot be used with skip, bits, len_of, count, const, with or serialize_with",
+            ));
+        }
+
+        if self.assert.is_some() && (self.skip.is_some() || self.bits.is_some()) {
+            return Err(syn::Error::new(
+                span,
+                "assert cannot be used with skip or bits",
+            ));
+        }
+
+        if self.message.is_some() && self.assert.is_none() {
+            return Err(syn::Error::new(
+                span,
+                "message can only be used with an assert attribute",
+            ));
+        }
+
+        if self.align == Some(0) {
+            return Err(syn::Error::new(span, "align must be greater than 0"));
+        }
+
+        if self.has_padding() && self.bits.is_some() {
+            return Err(syn::Error::new(
+                span,
+                "pad_before, pad_after and align cannot be used with bits",
+            ));
+        }
+
         if self.bits.is_some() && !is_valid_bit_field_type(&self.ty) {
             return Err(syn::Error::new(
                 span,
@@ -113,9 +614,44 @@ impl FieldAttributes {
     }
 }
 
+/// The expression of an `assert` attribute, along with the text it was written as.
+#[derive(Debug)]
+pub struct Assertion {
+    pub expr: syn::Expr,
+    pub text: String,
+}
+
+impl FromMeta for Assertion {
+    fn from_expr(expr: &syn::Expr) -> darling::Result<Self> {
+        match expr {
+            syn::Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Str(lit),
+                ..
+            }) => Ok(Self {
+                expr: lit.parse()?,
+                text: lit.value(),
+            }),
+            syn::Expr::Group(group) => Self::from_expr(&group.expr),
+            expr => Ok(Self {
+                expr: expr.clone(),
+                text: quote!(#expr).to_string(),
+            }),
+        }
+    }
+}
+
 #[derive(Debug, FromVariant)]
 #[darling(attributes(binja))]
 pub struct VariantAttributes {
     #[allow(unused)]
     pub ident: syn::Ident,
+
+    // #[binja(tag = 0x10)] / #[binja(tag = b"PNG")] / #[binja(tag = consts::PNG)]
+    pub tag: Option<syn::Expr>,
+}
+
+impl VariantAttributes {
+    pub fn tag(&self) -> Option<&syn::Expr> {
+        self.tag.as_ref()
+    }
 }
@@ -30,8 +30,8 @@ pub fn gen_bit_field_serialization(
         code.push(quote! {
             if( #field_expr >> #bits) != 0 {
                 return Err(::binja::error::Error::Overflow{
-                    value: format!("{:#x}", #field_expr),
-                    max: format!("{:#x}", binja::bit_mask!(#bits) ),
+                    value: ::binja::__private::format!("{:#x}", #field_expr),
+                    max: ::binja::__private::format!("{:#x}", ::binja::bit_mask!(#bits) ),
                 });
             }
         });
@@ -1,14 +1,12 @@
-use std::vec;
-
 use darling::FromVariant;
 use proc_macro2::{Span, TokenStream};
 use quote::quote;
 use syn::{Expr, parse_quote, token::Eq};
 
 use crate::{
-    attribute::{EnumAttributes, VariantAttributes},
+    attribute::{EnumAttributes, PaddingOptions, VariantAttributes},
     bounds::add_trait_bounds,
-    derive_struct::{gen_par_fields, gen_ser_fields},
+    derive_struct::{gen_par_fields, gen_ser_fields, with_config},
 };
 
 pub fn generate_enum_binary_serialize(
@@ -23,16 +21,33 @@ pub fn generate_enum_binary_serialize(
         add_trait_bounds(generics, parse_quote! { ::binja::BinarySerialize });
     let (impl_generics, ty_generics, where_clause) = generics_with_bounds.split_for_impl();
 
-    let variant_arms = generate_enum_serialize_variants(&data.variants, attr)?;
+    let tags = get_variant_tags(&data.variants, attr)?;
+    let variant_arms = generate_enum_serialize_variants(&data.variants, attr, &tags)?;
+    let tags_check = tags.gen_duplicates_check(&data.variants, attr);
 
-    let expand = quote! {
-         #[allow(unused_variables)]
-       impl #impl_generics ::binja::BinarySerialize for #name #ty_generics #where_clause {
-            fn binary_serialize(&self, serializer: &mut binja::BinarySerializer) -> binja::error::Result<()> {
+    // if enum have #[binja(big_endian)] or #[binja(little_endian)], use it for its whole value
+    let code = with_config(
+        &attr.endianness()?.into_iter().collect::<Vec<_>>(),
+        quote! { serializer },
+        quote! {
+            serializer.nested(|serializer| {
                 match self {
                     #variant_arms
                 }
                 Ok(())
+            })
+        },
+    );
+
+    let expand = quote! {
+         #[allow(unused_variables)]
+       impl #impl_generics ::binja::BinarySerialize for #name #ty_generics #where_clause {
+            fn binary_serialize<__S: binja::Sink>(
+                &self,
+                serializer: &mut binja::BinarySerializer<__S>,
+            ) -> binja::error::Result<()> {
+                #tags_check
+                #code
             }
         }
     };
@@ -43,23 +58,18 @@ pub fn generate_enum_binary_serialize(
 fn generate_enum_serialize_variants(
     variants: &syn::punctuated::Punctuated<syn::Variant, syn::token::Comma>,
     attr: &EnumAttributes,
+    tags: &VariantTags,
 ) -> syn::Result<TokenStream> {
     let repr = attr.repr();
     let untagged = attr.untagged();
-    let mut current_value: isize = -1;
+    let tag_ty = tags.ty(&repr);
 
     let mut code = Vec::new();
 
-    for variant in variants {
+    for (i, variant) in variants.iter().enumerate() {
         let variant_ident = &variant.ident;
 
-        // leave it here to validate variant attributes
-        let _attr = VariantAttributes::from_variant(variant)?;
-
-        get_enum_value(&mut current_value, &variant.discriminant);
-
-        // Create a literal with the correct suffix (e.g., 1i8)
-        let v_lit = syn::LitInt::new(&format!("{}{}", current_value, repr), Span::call_site());
+        let tag = tags.value(i, &repr);
 
         // enum TestEnum {
         //     AA,
@@ -72,12 +82,14 @@ fn generate_enum_serialize_variants(
         let (pat, serialize_fields) = match &variant.fields {
             syn::Fields::Named(fields) => {
                 // code to run
-                let (fields_names, fields_ser_code) = gen_ser_fields(&fields.named)?;
+                let (fields_names, fields_ser_code) =
+                    gen_ser_fields(&fields.named, PaddingOptions::default())?;
 
                 (quote! {{#fields_names}}, fields_ser_code)
             }
             syn::Fields::Unnamed(fields) => {
-                let (fields_names, fields_ser_code) = gen_ser_fields(&fields.unnamed)?;
+                let (fields_names, fields_ser_code) =
+                    gen_ser_fields(&fields.unnamed, PaddingOptions::default())?;
                 (quote! {(#fields_names)}, fields_ser_code)
             }
 
@@ -88,7 +100,7 @@ fn generate_enum_serialize_variants(
             quote! {}
         } else {
             quote! {
-                let value = #v_lit;
+                let value: #tag_ty = #tag;
                 ::binja::BinarySerialize::binary_serialize(&value, serializer)?;
             }
         };
@@ -120,12 +132,31 @@ pub fn generate_enum_binary_parse(
         add_trait_bounds(generics, parse_quote! { ::binja::BinaryParse });
     let (impl_generics, ty_generics, where_clause) = generics_with_bounds.split_for_impl();
 
-    let parse_code = gen_par_variants(&data.variants, attr)?;
+    let tags = get_variant_tags(&data.variants, attr)?;
+    let parse_code = gen_par_variants(&data.variants, attr, &tags)?;
+    let tags_check = tags.gen_duplicates_check(&data.variants, attr);
+    let name_str = name.to_string();
+
+    // if enum have #[binja(big_endian)] or #[binja(little_endian)], use it for its whole value
+    let code = with_config(
+        &attr.endianness()?.into_iter().collect::<Vec<_>>(),
+        quote! { parser },
+        quote! {
+            parser.nested(|parser| {
+                #parse_code
+            })
+        },
+    );
 
     let expand = quote! {
         impl #impl_generics ::binja::BinaryParse for #name #ty_generics #where_clause{
-            fn binary_parse(parser: &mut ::binja::BinaryParser) -> ::binja::error::Result<Self> {
-                #parse_code
+            fn binary_parse<'__de, __S: ::binja::Source<'__de>>(
+                parser: &mut ::binja::BinaryParser<'__de, __S>,
+            ) -> ::binja::error::Result<Self> {
+                #tags_check
+                parser.with_path(::binja::error::PathSegment::Type(#name_str), |parser| {
+                    #code
+                })
             }
         }
     };
@@ -136,54 +167,47 @@ pub fn generate_enum_binary_parse(
 fn gen_par_variants(
     variants: &syn::punctuated::Punctuated<syn::Variant, syn::token::Comma>,
     attrs: &EnumAttributes,
+    tags: &VariantTags,
 ) -> syn::Result<TokenStream> {
-    let mut current_value: isize = -1;
-    let mut seen_values = vec![];
+    let repr = attrs.repr();
 
     let mut variant_arms = Vec::new();
 
-    for variant in variants {
+    for (i, variant) in variants.iter().enumerate() {
         let variant_ident = &variant.ident;
 
-        // leave it here to validate variant attributes
-        let _attr = VariantAttributes::from_variant(variant)?;
-
-        get_enum_value(&mut current_value, &variant.discriminant);
-
-        // Create a literal with the correct suffix (e.g., 1i8)
-        let v_lit = syn::LitInt::new(
-            &format!("{}{}", current_value, attrs.repr()),
-            Span::call_site(),
-        );
+        let v_lit = tags.pattern(i, &repr);
 
-        seen_values.push(current_value);
+        let variant_path = variant_ident.to_string();
 
         match &variant.fields {
             syn::Fields::Unit => variant_arms.push(quote! {
                 #v_lit => Ok(Self::#variant_ident),
             }),
             syn::Fields::Unnamed(fields) => {
-                let (fields_names, fields_code) = gen_par_fields(&fields.unnamed)?;
+                let (fields_names, fields_code) =
+                    gen_par_fields(&fields.unnamed, PaddingOptions::default())?;
 
                 variant_arms.push(quote! {
-                    #v_lit => {
+                    #v_lit => parser.with_path(::binja::error::PathSegment::Variant(#variant_path), |parser| {
                         #fields_code
                         Ok(Self::#variant_ident(
                             #fields_names
                         ))
-                    }
+                    }),
                 });
             }
             syn::Fields::Named(fields) => {
-                let (fields_names, fields_code) = gen_par_fields(&fields.named)?;
+                let (fields_names, fields_code) =
+                    gen_par_fields(&fields.named, PaddingOptions::default())?;
 
                 variant_arms.push(quote! {
-                    #v_lit => {
+                    #v_lit => parser.with_path(::binja::error::PathSegment::Variant(#variant_path), |parser| {
                         #fields_code
                         Ok(Self::#variant_ident {
                             #fields_names
                         })
-                    }
+                    }),
                 });
                 // let parsers = gen_par_named_fields(fields, false);
                 // quote! {
@@ -196,10 +220,7 @@ fn gen_par_variants(
     }
 
     // Format expected values as a human-readable string
-    let mut expected = seen_values
-        .iter()
-        .map(|v| v.to_string())
-        .collect::<Vec<_>>();
+    let mut expected = tags.expected();
 
     let expected_str = match expected.len() {
         0 => "".to_string(),
@@ -210,29 +231,36 @@ fn gen_par_variants(
         }
     };
 
-    let repr_ty: syn::Type = syn::parse_str(&attrs.repr()).unwrap();
+    let tag_ty = tags.ty(&repr);
 
     let current_value_code = if attrs.untagged() {
-        let v_lit = syn::LitInt::new(
-            &format!("{}{}", seen_values.first().unwrap_or(&0), attrs.repr()),
-            Span::call_site(),
-        );
+        let first = if tags.tags.is_empty() {
+            int_tag(0, &repr)
+        } else {
+            tags.value(0, &repr)
+        };
         quote! {
-            let current_value: #repr_ty = #v_lit;
+            let current_value: #tag_ty = #first;
         }
     } else {
         quote! {
-            let current_value: #repr_ty = ::binja::BinaryParse::binary_parse(parser)?;
+            let current_value: #tag_ty = ::binja::BinaryParse::binary_parse(parser)?;
         }
     };
 
+    let found = if tags.bytes_len.is_some() {
+        quote! { ::binja::__private::format!("{:02x?}", x) }
+    } else {
+        quote! { ::binja::__private::format!("{}", x) }
+    };
+
     let expand = quote! {
         #current_value_code
         match current_value{
             #(#variant_arms)*
             x => Err(::binja::error::Error::InvalidVariant {
-                expected: #expected_str.to_string(),
-                found: format!("{}", x),
+                expected: ::binja::__private::String::from(#expected_str),
+                found: #found,
             }),
         }
     };
@@ -240,38 +268,237 @@ fn gen_par_variants(
     Ok(expand)
 }
 
-fn get_enum_value(current_value: &mut isize, discriminant: &Option<(Eq, Expr)>) {
-    if let Some((_, expr)) = discriminant {
-        match expr {
-            syn::Expr::Lit(syn::ExprLit {
-                lit: syn::Lit::Int(lit_int),
-                ..
-            }) => {
-                let value = lit_int.base10_parse::<isize>().unwrap();
-                *current_value = value;
+/// The tag written before the fields of a variant.
+enum Tag {
+    /// An integer of the enum's `repr` type: the variant's tag or its discriminant.
+    Int(i128),
+    /// A byte string, written as is.
+    Bytes(Vec<u8>),
+    /// A constant path, only known once compiled.
+    Const(syn::Path),
+}
+
+/// The tags of the variants of an enum, in order.
+struct VariantTags {
+    tags: Vec<Tag>,
+    // the length of the tags, if they are byte strings
+    bytes_len: Option<usize>,
+}
+
+impl VariantTags {
+    /// Returns the type tags are written as.
+    fn ty(&self, repr: &str) -> TokenStream {
+        match self.bytes_len {
+            Some(len) => quote! { [u8; #len] },
+            None => {
+                let repr_ty: syn::Type = syn::parse_str(repr).unwrap();
+                quote! { #repr_ty }
             }
-            syn::Expr::Unary(syn::ExprUnary {
-                op: syn::UnOp::Neg(_),
-                expr,
-                ..
-            }) => {
-                if let syn::Expr::Lit(syn::ExprLit {
-                    lit: syn::Lit::Int(lit_int),
-                    ..
-                }) = &**expr
+        }
+    }
+
+    /// Returns the expression of the tag of variant `i`.
+    fn value(&self, i: usize, repr: &str) -> TokenStream {
+        match &self.tags[i] {
+            Tag::Int(value) => int_tag(*value, repr),
+            Tag::Bytes(bytes) => quote! { [#(#bytes),*] },
+            Tag::Const(path) => quote! { #path },
+        }
+    }
+
+    /// Returns the match pattern of the tag of variant `i`.
+    fn pattern(&self, i: usize, repr: &str) -> TokenStream {
+        match &self.tags[i] {
+            // a path pattern naming no constant would match anything, so compare instead
+            Tag::Const(path) => quote! { value if value == #path },
+            _ => self.value(i, repr),
+        }
+    }
+
+    /// Returns the tags, formatted for `InvalidVariant` errors.
+    fn expected(&self) -> Vec<String> {
+        self.tags
+            .iter()
+            .map(|tag| match tag {
+                Tag::Int(value) => value.to_string(),
+                Tag::Bytes(bytes) => format!("{bytes:02x?}"),
+                Tag::Const(path) => path
+                    .segments
+                    .iter()
+                    .map(|segment| segment.ident.to_string())
+                    .collect::<Vec<_>>()
+                    .join("::"),
+            })
+            .collect()
+    }
+
+    /// Returns compile time checks that const tags differ from the other tags, as they
+    /// can't be compared while deriving.
+    fn gen_duplicates_check(
+        &self,
+        variants: &syn::punctuated::Punctuated<syn::Variant, syn::token::Comma>,
+        attrs: &EnumAttributes,
+    ) -> TokenStream {
+        let repr = attrs.repr();
+        let mut checks = Vec::new();
+
+        for (i, a) in variants.iter().enumerate() {
+            for (j, b) in variants.iter().enumerate().skip(i + 1) {
+                if !matches!(self.tags[i], Tag::Const(_)) && !matches!(self.tags[j], Tag::Const(_))
                 {
-                    let value = lit_int.base10_parse::<isize>().unwrap();
-                    *current_value = -value;
-                } else {
-                    panic!("Unsupported expression in unary negation");
+                    continue;
                 }
+
+                let message = format!("variants `{}` and `{}` have the same tag", a.ident, b.ident);
+                let (a, b) = (self.value(i, &repr), self.value(j, &repr));
+                checks.push(match self.bytes_len {
+                    Some(_) => quote! {
+                        ::core::assert!(!::binja::__private::tags_eq(&#a, &#b), #message);
+                    },
+                    None => quote! {
+                        ::core::assert!(#a != #b, #message);
+                    },
+                });
             }
-            _ => {
-                panic!("Only literal integer discriminants are supported for now");
+        }
+
+        if checks.is_empty() {
+            return quote! {};
+        }
+
+        quote! {
+            const {
+                #(#checks)*
+            };
+        }
+    }
+}
+
+/// Returns the tags of the variants, given by `#[binja(tag = ...)]` or else by their
+/// discriminant, rejecting duplicates.
+fn get_variant_tags(
+    variants: &syn::punctuated::Punctuated<syn::Variant, syn::token::Comma>,
+    attrs: &EnumAttributes,
+) -> syn::Result<VariantTags> {
+    let mut current_value = Some(-1);
+    let mut tags: Vec<Tag> = Vec::new();
+    let mut bytes_len = None;
+
+    for variant in variants {
+        let attr = VariantAttributes::from_variant(variant)?;
+
+        get_enum_value(&mut current_value, &variant.discriminant);
+
+        let tag = match attr.tag() {
+            Some(tag) if attrs.untagged() => {
+                return Err(syn::Error::new_spanned(
+                    tag,
+                    "tag cannot be used in an untagged enum",
+                ));
             }
+            Some(tag) => parse_tag(tag)?,
+            None => Tag::Int(current_value.ok_or_else(|| {
+                syn::Error::new_spanned(
+                    &variant.ident,
+                    "Only literal integer discriminants are supported, use #[binja(tag = ...)] instead",
+                )
+            })?),
+        };
+
+        if let Tag::Bytes(bytes) = &tag {
+            match bytes_len {
+                Some(len) if len != bytes.len() => {
+                    return Err(syn::Error::new_spanned(
+                        &variant.ident,
+                        format!("byte string tags must have the same length, expected {len} bytes"),
+                    ));
+                }
+                _ => bytes_len = Some(bytes.len()),
+            }
+        }
+
+        let duplicate = tags
+            .iter()
+            .zip(variants)
+            .find(|(other, _)| match (other, &tag) {
+                (Tag::Int(a), Tag::Int(b)) => a == b,
+                (Tag::Bytes(a), Tag::Bytes(b)) => a == b,
+                _ => false,
+            });
+        if let Some((_, other)) = duplicate {
+            return Err(syn::Error::new_spanned(
+                &variant.ident,
+                format!("variant has the same tag as `{}`", other.ident),
+            ));
         }
+
+        tags.push(tag);
+    }
+
+    // byte string and integer tags can't be mixed
+    if bytes_len.is_some()
+        && let Some((_, variant)) = tags
+            .iter()
+            .zip(variants)
+            .find(|(tag, _)| matches!(tag, Tag::Int(_)))
+    {
+        return Err(syn::Error::new_spanned(
+            &variant.ident,
+            "variant needs a byte string or const tag, as other variants have byte string tags",
+        ));
+    }
+
+    Ok(VariantTags { tags, bytes_len })
+}
+
+/// Reads a `#[binja(tag = ...)]` value: an integer, a byte string or a const path.
+fn parse_tag(expr: &Expr) -> syn::Result<Tag> {
+    match expr {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Int(lit_int),
+            ..
+        }) => Ok(Tag::Int(lit_int.base10_parse::<i128>()?)),
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::ByteStr(lit_byte_str),
+            ..
+        }) => Ok(Tag::Bytes(lit_byte_str.value())),
+        syn::Expr::Unary(syn::ExprUnary {
+            op: syn::UnOp::Neg(_),
+            expr,
+            ..
+        }) => match parse_tag(expr)? {
+            Tag::Int(value) => Ok(Tag::Int(-value)),
+            _ => Err(syn::Error::new_spanned(
+                expr,
+                "only integers can be negated",
+            )),
+        },
+        syn::Expr::Path(syn::ExprPath {
+            qself: None, path, ..
+        }) => Ok(Tag::Const(path.clone())),
+        _ => Err(syn::Error::new_spanned(
+            expr,
+            "tag must be an integer, a byte string or a const path",
+        )),
+    }
+}
+
+/// Returns an integer literal with the `repr` suffix (e.g., 1i8).
+fn int_tag(value: i128, repr: &str) -> TokenStream {
+    let lit = syn::LitInt::new(&format!("{value}{repr}"), Span::call_site());
+    quote! { #lit }
+}
+
+/// Advances `current_value` to the discriminant of the next variant, or `None` once it
+/// can't be known while deriving.
+fn get_enum_value(current_value: &mut Option<i128>, discriminant: &Option<(Eq, Expr)>) {
+    if let Some((_, expr)) = discriminant {
+        *current_value = match parse_tag(expr) {
+            Ok(Tag::Int(value)) => Some(value),
+            _ => None,
+        };
     } else {
         // If no discriminant is provided, use the current value
-        *current_value += 1;
+        *current_value = current_value.map(|value| value + 1);
     }
 }
@@ -1,10 +1,9 @@
-use darling::FromField;
 use proc_macro2::{Span, TokenStream};
 use quote::{ToTokens, quote};
 use syn::{parse_quote, spanned::Spanned};
 
 use crate::{
-    attribute::{FieldAttributes, StructAttributes},
+    attribute::{FieldAttributes, PaddingOptions, StructAttributes},
     bit_field::{flush_bit_field_at_end, flush_bit_field_if_needed, gen_bit_field_serialization},
     bounds::add_trait_bounds,
 };
@@ -23,10 +22,12 @@ pub fn generate_struct_binary_serialize(
         add_trait_bounds(generics, parse_quote! { ::binja::BinarySerialize });
     let (impl_generics, ty_generics, where_clause) = generics_with_bounds.split_for_impl();
 
+    let padding = attributes.padding();
+
     let fields_token = match &data.fields {
         // struct Example { field: String }
         syn::Fields::Named(fields_named) => {
-            let (fields_names, field_ser_code) = gen_ser_fields(&fields_named.named)?;
+            let (fields_names, field_ser_code) = gen_ser_fields(&fields_named.named, padding)?;
 
             quote! {
                let #struct_name { #fields_names } = self;
@@ -35,7 +36,7 @@ pub fn generate_struct_binary_serialize(
         }
         // struct Example(String) , struct Example(String, String)
         syn::Fields::Unnamed(fields_unnamed) => {
-            let (fields_names, field_ser_code) = gen_ser_fields(&fields_unnamed.unnamed)?;
+            let (fields_names, field_ser_code) = gen_ser_fields(&fields_unnamed.unnamed, padding)?;
 
             quote! {
                 let #struct_name ( #fields_names ) = self;
@@ -48,14 +49,61 @@ pub fn generate_struct_binary_serialize(
         }
     };
 
+    // if struct have #[binja(magic = b"...")], write it before the fields
+    let magic = attributes.magic.as_ref().map(|magic| {
+        quote! { serializer.bytes(#magic)?; }
+    });
+
+    // if struct have #[binja(pad_to = N)], write padding up to N bytes after the fields
+    let (start, pad_to) = match attributes.pad_to {
+        Some(pad_to) => {
+            let fill = padding.fill;
+            (
+                quote! { let start = serializer.size(); },
+                quote! {
+                    let written = serializer.size() - start;
+                    if written > #pad_to {
+                        return Err(::binja::error::Error::InvalidLength {
+                            expected: ::binja::__private::format!("at most {} bytes", #pad_to),
+                            found: ::binja::__private::format!("{} bytes", written),
+                        });
+                    }
+                    serializer.pad(#pad_to - written, #fill)?;
+                },
+            )
+        }
+        None => (quote! {}, quote! {}),
+    };
+
+    // if struct have #[binja(big_endian)] or #[binja(little_endian)], use it for its whole value
+    let code = with_config(
+        &attributes.endianness()?.into_iter().collect::<Vec<_>>(),
+        quote! { serializer },
+        quote! {
+            serializer.nested(|serializer| {
+                #start
+                #magic
+                #fields_token
+                #pad_to
+                Ok(())
+            })
+        },
+    );
+
+    let fixed_size_impl = gen_fixed_encoded_size(&data.fields, attributes)?;
+
     let expand = quote! {
         #[allow(unused_variables)]
         impl #impl_generics ::binja::BinarySerialize for #struct_name #ty_generics #where_clause {
-            fn binary_serialize(&self, serializer: &mut ::binja::BinarySerializer) -> ::binja::error::Result<()> {
-                #fields_token
-                Ok(())
+            fn binary_serialize<__S: ::binja::Sink>(
+                &self,
+                serializer: &mut ::binja::BinarySerializer<__S>,
+            ) -> ::binja::error::Result<()> {
+                #code
             }
         }
+
+        #fixed_size_impl
     };
 
     Ok(expand.into())
@@ -72,37 +120,86 @@ pub fn generate_struct_binary_parse(
     let generics_with_bounds = add_trait_bounds(generics, parse_quote! { ::binja::BinaryParse });
     let (impl_generics, ty_generics, where_clause) = generics_with_bounds.split_for_impl();
 
+    let padding = attributes.padding();
+
     let code = match &data.fields {
         // struct Example { field: String }
         syn::Fields::Named(fields_named) => {
-            let (fields_names, fields_par_code) = gen_par_fields(&fields_named.named)?;
+            let (fields_names, fields_par_code) = gen_par_fields(&fields_named.named, padding)?;
             quote! {
                 #fields_par_code
-                Ok(Self {
+                let value = Self {
                     #fields_names
-                })
+                };
             }
         }
 
         // struct Example(String) , struct Example(String, String)
         syn::Fields::Unnamed(fields_unnamed) => {
-            let (fields_names, fields_par_code) = gen_par_fields(&fields_unnamed.unnamed)?;
+            let (fields_names, fields_par_code) = gen_par_fields(&fields_unnamed.unnamed, padding)?;
             quote! {
                 #fields_par_code
-                Ok(Self(
+                let value = Self(
                     #fields_names
-                ))
+                );
             }
         }
 
         // struct Example;
-        syn::Fields::Unit => quote! {Ok(Self {})},
+        syn::Fields::Unit => quote! { let value = Self {}; },
     };
 
+    let name_str = name.to_string();
+
+    // if struct have #[binja(magic = b"...")], check it before the fields
+    let magic = attributes.magic.as_ref().map(|magic| {
+        quote! { parser.expect_bytes(#magic)?; }
+    });
+
+    // if struct have #[binja(pad_to = N)], skip the padding up to N bytes after the fields
+    let (start, pad_to) = match attributes.pad_to {
+        Some(pad_to) => {
+            let fill = padding.check_fill();
+            (
+                quote! { let start = parser.position(); },
+                quote! {
+                    let read = parser.position() - start;
+                    if read > #pad_to {
+                        return Err(::binja::error::Error::InvalidLength {
+                            expected: ::binja::__private::format!("at most {} bytes", #pad_to),
+                            found: ::binja::__private::format!("{} bytes", read),
+                        });
+                    }
+                    parser.skip_padding(#pad_to - read, #fill)?;
+                },
+            )
+        }
+        None => (quote! {}, quote! {}),
+    };
+
+    // if struct have #[binja(big_endian)] or #[binja(little_endian)], use it for its whole value
+    let code = with_config(
+        &attributes.endianness()?.into_iter().collect::<Vec<_>>(),
+        quote! { parser },
+        quote! {
+            parser.nested(|parser| {
+                #start
+                #magic
+                #code
+                #pad_to
+                Ok(value)
+            })
+        },
+    );
+
     let expand = quote! {
         impl #impl_generics ::binja::BinaryParse for #name #ty_generics #where_clause{
-            fn binary_parse(parser: &mut ::binja::BinaryParser) -> ::binja::error::Result<Self> {
-                #code
+            fn binary_parse<'__de, __S: ::binja::Source<'__de>>(
+                parser: &mut ::binja::BinaryParser<'__de, __S>,
+            ) -> ::binja::error::Result<Self> {
+                parser.with_path(::binja::error::PathSegment::Type(#name_str), |parser| {
+                    #code
+                })
             }
         }
     };
@@ -110,24 +207,168 @@ pub fn generate_struct_binary_parse(
     Ok(expand.into())
 }
 
+/// Implements `FixedEncodedSize` for the struct, adding up the size of every field.
+///
+/// Each field type gets a higher-ranked `FixedEncodedSize` bound: such bounds are only
+/// checked when the impl is used, so structs with variable-size fields still compile and
+/// simply don't implement the trait.
+fn gen_fixed_encoded_size(
+    fields: &syn::Fields,
+    attributes: &StructAttributes,
+) -> syn::Result<TokenStream> {
+    let struct_name = &attributes.ident;
+    let mut generics = attributes.generics.clone();
+    let where_clause = generics.make_where_clause();
+
+    // padded to a given size, whatever its fields are
+    if let Some(pad_to) = attributes.pad_to {
+        let (impl_generics, ty_generics, where_clause) = attributes.generics.split_for_impl();
+        return Ok(quote! {
+            impl #impl_generics ::binja::FixedEncodedSize for #struct_name #ty_generics #where_clause {
+                const SIZE: usize = #pad_to;
+            }
+        });
+    }
+
+    let mut sizes = Vec::new();
+    if let Some(magic) = &attributes.magic {
+        let len = magic.value().len();
+        sizes.push(quote! { #len });
+    }
+    // bits of consecutive bit fields, packed together on the wire
+    let mut bit_field_bits = 0usize;
+
+    for f in fields {
+        let attrs = FieldAttributes::parse(f)?;
+
+        // an aligned field has no fixed size, so neither has the struct
+        if attrs.align().is_some() {
+            return Ok(quote! {});
+        }
+
+        let pad = attrs.pad_before().unwrap_or(0) + attrs.pad_after().unwrap_or(0);
+        if pad > 0 {
+            sizes.push(quote! { #pad });
+            // padding ends a run of bit fields
+            let bit_field_bytes = bit_field_bits.div_ceil(8);
+            if bit_field_bytes > 0 {
+                sizes.push(quote! { #bit_field_bytes });
+            }
+            bit_field_bits = 0;
+        }
+
+        if attrs.skip() {
+            continue;
+        }
+
+        // a varint, custom or mapped field has no fixed size, so neither has the struct
+        if attrs.varint()
+            || attrs.has_custom_codec()
+            || attrs.map().is_some()
+            || attrs.try_map().is_some()
+            || attrs.into_type().is_some()
+        {
+            return Ok(quote! {});
+        }
+
+        if let Some(bits) = attrs.bits() {
+            bit_field_bits += bits as usize;
+            continue;
+        }
+
+        let bit_field_bytes = bit_field_bits.div_ceil(8);
+        if bit_field_bytes > 0 {
+            sizes.push(quote! { #bit_field_bytes });
+        }
+        bit_field_bits = 0;
+
+        let ty = &f.ty;
+        where_clause.predicates.push(parse_quote! {
+            for<'__binja> #ty: ::binja::FixedEncodedSize
+        });
+        sizes.push(quote! { <#ty as ::binja::FixedEncodedSize>::SIZE });
+    }
+
+    let bit_field_bytes = bit_field_bits.div_ceil(8);
+    if bit_field_bytes > 0 {
+        sizes.push(quote! { #bit_field_bytes });
+    }
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::binja::FixedEncodedSize for #struct_name #ty_generics #where_clause {
+            const SIZE: usize = 0 #(+ #sizes)*;
+        }
+    })
+}
+
 pub fn gen_ser_fields(
     fields: &syn::punctuated::Punctuated<syn::Field, syn::Token![,]>,
+    padding: PaddingOptions,
 ) -> syn::Result<(TokenStream, TokenStream)> {
     let mut field_names = Vec::new();
     let mut code = Vec::new();
     let mut bit_field_declared = false;
     let mut bit_offset = 0u8;
+    // `len_of` fields whose target is not serialized yet: target, reservation, length type
+    // and config builders
+    let mut pending_lengths = Vec::new();
+    // fields holding the count of a later `count` field: count field, counted field
+    let filled_counts = get_filled_counts(fields)?;
 
     for (i, f) in fields.iter().enumerate() {
         // Check if the field has attributes
-        let attrs = FieldAttributes::from_field(f)?;
+        let attrs = FieldAttributes::parse(f)?;
         attrs.validate(f.span())?;
 
         let field_expr = get_field_expr(f, i);
         field_names.push(field_expr.clone());
+        let counted = filled_counts
+            .iter()
+            .find(|(count, _)| f.ident.as_ref() == Some(count))
+            .map(|(_, counted)| counted);
+
+        // if field have #[binja(pad_before = N)] or #[binja(align = N)], write padding first
+        let fill = padding.fill;
+        if attrs.has_padding() {
+            flush_bit_field_if_needed(&mut code, &mut bit_offset);
+        }
+        if let Some(pad_before) = attrs.pad_before() {
+            code.push(quote! { serializer.pad(#pad_before, #fill)?; });
+        }
+        if let Some(align) = attrs.align() {
+            code.push(quote! { serializer.align(#align, #fill)?; });
+        }
+        // if field have #[binja(pad_after = N)], write padding after it
+        let pad_after = attrs.pad_after().map(|pad_after| {
+            quote! { serializer.pad(#pad_after, #fill)?; }
+        });
 
-        // skip field
+        // skip field, or computed field
         if attrs.skip() {
+            code.extend(pad_after);
+            continue;
+        }
+
+        // if field have #[binja(len_of = "body")], reserve it until `body` is written
+        if let Some(target) = attrs.len_of() {
+            check_len_of_target(fields, i, target)?;
+            flush_bit_field_if_needed(&mut code, &mut bit_offset);
+
+            let reservation = syn::Ident::new(&format!("reservation_{target}"), Span::mixed_site());
+            let ty = &f.ty;
+            code.push(quote! {
+                let #reservation = serializer
+                    .reserve(<#ty as ::binja::FixedEncodedSize>::SIZE)?;
+            });
+            pending_lengths.push((
+                target.clone(),
+                reservation,
+                ty.clone(),
+                attrs.config_builders(),
+            ));
+            code.extend(pad_after);
             continue;
         }
 
@@ -162,10 +403,123 @@ pub fn gen_ser_fields(
             flush_bit_field_if_needed(&mut code, &mut bit_offset);
 
             // serialize the current field
-            code.push(quote! {
-                ::binja::BinarySerialize::binary_serialize(#field_expr, serializer)?;
-            })
+            let ser_code = if let Some(counted) = counted {
+                // the count of a later #[binja(count = "field")] is filled in from its length
+                let ty = &f.ty;
+                quote! {{
+                    let count: #ty = ::binja::__private::FromLength::from_length(
+                        ::binja::__private::SerializeCounted::counted_len(#counted),
+                    )?;
+                    ::binja::BinarySerialize::binary_serialize(&count, serializer)
+                }}
+            } else if let Some(serialize_with) = attrs.serialize_with() {
+                // if field have #[binja(serialize_with = "fn")] or #[binja(with = "module")]
+                quote! { #serialize_with(#field_expr, serializer) }
+            } else if let Some(constant) = attrs.constant() {
+                // if field have #[binja(const = value)], the value is written in its place
+                let ty = &f.ty;
+                quote! {{
+                    let value: #ty = #constant;
+                    ::binja::BinarySerialize::binary_serialize(&value, serializer)
+                }}
+            } else if let Some(count) = attrs.count() {
+                // if field have #[binja(count = "expr")], check the count matches its length
+                let check = if filled_counts
+                    .iter()
+                    .any(|(_, counted)| f.ident.as_ref() == Some(counted))
+                {
+                    quote! {}
+                } else {
+                    quote! {
+                        let count = ::binja::__private::IntoLength::into_length(#count)?;
+                        let len = ::binja::__private::SerializeCounted::counted_len(#field_expr);
+                        if count != len {
+                            return Err(::binja::error::Error::InvalidLength {
+                                expected: ::binja::__private::format!("{} elements", count),
+                                found: ::binja::__private::format!("{} elements", len),
+                            });
+                        }
+                    }
+                };
+                quote! {{
+                    #check
+                    ::binja::__private::SerializeCounted::serialize_counted(#field_expr, serializer)
+                }}
+            } else if let Some(condition) = attrs.condition() {
+                // if field have #[binja(if = "expr")], it is written only when `expr` holds
+                let name = get_field_name(f, i);
+                let serialize_value = gen_ser_value(&attrs, quote! { value });
+                quote! {
+                    match (#condition, #field_expr) {
+                        (true, ::core::option::Option::Some(value)) => #serialize_value,
+                        (false, ::core::option::Option::None) => Ok(()),
+                        (true, ::core::option::Option::None) => {
+                            Err(::binja::error::Error::InvalidValue {
+                                expected: ::binja::__private::format!(
+                                    "Some for `{}`, as its condition holds", #name
+                                ),
+                                found: ::binja::__private::String::from("None"),
+                            })
+                        }
+                        (false, ::core::option::Option::Some(_)) => {
+                            Err(::binja::error::Error::InvalidValue {
+                                expected: ::binja::__private::format!(
+                                    "None for `{}`, as its condition doesn't hold", #name
+                                ),
+                                found: ::binja::__private::String::from("Some"),
+                            })
+                        }
+                    }
+                }
+            } else {
+                gen_ser_value(&attrs, field_expr.clone())
+            };
+            let ser_code = with_field_config(&attrs, quote! { serializer }, ser_code);
+
+            // if an earlier field is its #[binja(len_of)], that field is patched in afterwards
+            let pending = pending_lengths
+                .iter()
+                .position(|(target, _, _, _)| f.ident.as_ref() == Some(target))
+                .map(|pending| pending_lengths.remove(pending));
+
+            // if field have #[binja(size = "expr")], check it is written in exactly that size,
+            // unless the size is computed by a len_of field
+            let ser_code = if let Some(size) = attrs.size().filter(|_| pending.is_none()) {
+                quote! {{
+                    let size = ::binja::__private::IntoLength::into_length(#size)?;
+                    let start = serializer.size();
+                    #ser_code?;
+                    let written = serializer.size() - start;
+                    if written != size {
+                        return Err(::binja::error::Error::InvalidLength {
+                            expected: ::binja::__private::format!("{} bytes", size),
+                            found: ::binja::__private::format!("{} bytes", written),
+                        });
+                    }
+                }}
+            } else {
+                quote! {
+                    #ser_code?;
+                }
+            };
+
+            if let Some((_, reservation, ty, builders)) = pending {
+                code.push(quote! {{
+                    let start = serializer.size();
+                    #ser_code
+                    let written = serializer.size() - start;
+                    let length: #ty = ::binja::__private::FromLength::from_length(written)?;
+                    let config = serializer.config()#(.#builders())*;
+                    serializer.scoped_config(config, |serializer| {
+                        serializer.patch(#reservation, &length)
+                    })?;
+                }});
+            } else {
+                code.push(ser_code);
+            }
         }
+
+        code.extend(pad_after);
     }
 
     // if last field is a bit field smaller that 8 bits
@@ -184,6 +538,7 @@ pub fn gen_ser_fields(
 
 pub fn gen_par_fields(
     fields: &syn::punctuated::Punctuated<syn::Field, syn::Token![,]>,
+    padding: PaddingOptions,
 ) -> syn::Result<(TokenStream, TokenStream)> {
     let mut code = Vec::new();
     let mut fields_names = Vec::new();
@@ -192,10 +547,14 @@ pub fn gen_par_fields(
     let byte_var = quote! { bit_field };
 
     for (i, f) in fields.iter().enumerate() {
-        let attrs = FieldAttributes::from_field(f)?;
+        let attrs = FieldAttributes::parse(f)?;
         attrs.validate(f.span())?;
+        if let Some(target) = attrs.len_of() {
+            check_len_of_target(fields, i, target)?;
+        }
 
         let ident = get_field_expr(f, i);
+        let path = get_field_path(f, i);
         let is_bool = is_type_bool(&f.ty);
         let field_type = if is_bool {
             quote! { usize }
@@ -203,12 +562,41 @@ pub fn gen_par_fields(
             let ty = &f.ty;
             quote! { #ty }
         };
-        fields_names.push(ident.clone());
+
+        // if field have #[binja(pad_before = N)] or #[binja(align = N)], skip padding first
+        let fill = padding.check_fill();
+        if attrs.has_padding() {
+            bit_offset = 0;
+        }
+        if let Some(pad_before) = attrs.pad_before() {
+            code.push(quote! {
+                parser.with_path(#path, |parser| parser.skip_padding(#pad_before, #fill))?;
+            });
+        }
+        if let Some(align) = attrs.align() {
+            code.push(quote! {
+                parser.with_path(#path, |parser| parser.skip_to_alignment(#align, #fill))?;
+            });
+        }
+        // if field have #[binja(pad_after = N)], skip padding after it
+        let pad_after = attrs.pad_after().map(|pad_after| {
+            quote! {
+                parser.with_path(#path, |parser| parser.skip_padding(#pad_after, #fill))?;
+            }
+        });
 
         if attrs.skip() {
+            // if field have #[binja(calc = "expr")] or #[binja(skip, default = "expr")], its
+            // value is computed from the earlier fields
+            let value = match attrs.calc().or(attrs.default()) {
+                Some(expr) => with_fields_in_scope(&fields_names, expr),
+                None => quote! { ::core::default::Default::default() },
+            };
             code.push(quote! {
-                let #ident = Default::default();
+                let #ident = #value;
             });
+            code.extend(pad_after);
+            fields_names.push(ident);
             continue;
         }
 
@@ -228,7 +616,7 @@ pub fn gen_par_fields(
                 // Read a new byte if starting fresh or if no byte is loaded yet
                 if bit_offset % 8 == 0 {
                     code.push(quote! {
-                        let #byte_var: u8 = ::binja::BinaryParse::binary_parse(parser)?;
+                        let #byte_var: u8 = parser.with_path(#path, ::binja::BinaryParse::binary_parse)?;
                     });
                 }
 
@@ -273,10 +661,111 @@ pub fn gen_par_fields(
             // current field is not a bit field
             bit_offset = 0;
 
-            code.push(quote! {
-                let #ident = ::binja::BinaryParse::binary_parse(parser)?;
-            });
+            let par_code = if let Some(count) = attrs.count() {
+                // if field have #[binja(count = "expr")], parse that many elements
+                let count = with_fields_in_scope(&fields_names, count);
+                quote! {{
+                    let count = ::binja::__private::IntoLength::into_length(#count)?;
+                    ::binja::__private::ParseCounted::parse_counted(parser, count)
+                }}
+            } else if let Some(parse_with) = attrs.parse_with() {
+                // if field have #[binja(parse_with = "fn")] or #[binja(with = "module")]
+                quote! { #parse_with(parser) }
+            } else if let Some(constant) = attrs.constant() {
+                // if field have #[binja(const = value)], check the input holds the value
+                let ty = &f.ty;
+                quote! {{
+                    let value: #ty = #constant;
+                    ::binja::__private::expect_const(parser, &value)?;
+                    Ok(value)
+                }}
+            } else if let Some(ty) = attrs.into_type().filter(|_| attrs.has_mapping()) {
+                // if field have #[binja(into = "Type")] along with a mapping, the raw value
+                // has that type on the wire
+                quote! { <#ty as ::binja::BinaryParse>::binary_parse(parser) }
+            } else {
+                quote! { ::binja::BinaryParse::binary_parse(parser) }
+            };
+            let par_code = with_field_config(&attrs, quote! { parser }, par_code);
+
+            let name = get_field_name(f, i);
+            // if field have #[binja(map = "fn")] or #[binja(try_map = "fn")], the raw value
+            // is converted once parsed
+            let par_code = if let Some(map) = attrs.map() {
+                quote! { (#par_code).map(#map) }
+            } else if let Some(try_map) = attrs.try_map() {
+                quote! {
+                    (#par_code).and_then(|value| {
+                        (#try_map)(value).map_err(|error| ::binja::error::Error::Validation {
+                            field: ::binja::__private::String::from(#name),
+                            message: ::binja::__private::format!("{}", error),
+                        })
+                    })
+                }
+            } else {
+                par_code
+            };
+
+            // if field have #[binja(assert = "expr")], the value is checked once parsed
+            let par_code = match attrs.assert().zip(attrs.assert_message()) {
+                Some((assert, message)) => {
+                    let mut in_scope = fields_names.clone();
+                    in_scope.push(ident.clone());
+                    let assert = with_fields_in_scope(&in_scope, assert);
+                    quote! {{
+                        let #ident = #par_code?;
+                        if !#assert {
+                            return Err(::binja::error::Error::Validation {
+                                field: ::binja::__private::String::from(#name),
+                                message: ::binja::__private::String::from(#message),
+                            });
+                        }
+                        Ok(#ident)
+                    }}
+                }
+                None => par_code,
+            };
+
+            // if field have #[binja(size = "expr")], parse it from a bounded region, whose
+            // leftover bytes are skipped unless the field also have #[binja(exact)]
+            let par_code = match attrs.size() {
+                Some(size) => {
+                    let size = with_fields_in_scope(&fields_names, size);
+                    let finish = if attrs.exact() {
+                        quote! { finish_exact }
+                    } else {
+                        quote! { finish }
+                    };
+                    quote! {{
+                        let size = ::binja::__private::IntoLength::into_length(#size)?;
+                        let parser = &mut parser.take(size);
+                        let value = #par_code?;
+                        parser.#finish()?;
+                        Ok(value)
+                    }}
+                }
+                None => par_code,
+            };
+
+            // if field have #[binja(if = "expr")], it is read only when `expr` holds
+            if let Some(condition) = attrs.condition() {
+                let condition = with_fields_in_scope(&fields_names, condition);
+                code.push(quote! {
+                    let #ident = if #condition {
+                        ::core::option::Option::Some(parser.with_path(#path, |parser| #par_code)?)
+                    } else {
+                        ::core::option::Option::None
+                    };
+                });
+            } else {
+                code.push(quote! {
+                    let #ident = parser.with_path(#path, |parser| #par_code)?;
+                });
+            }
         }
+
+        code.extend(pad_after);
+        fields_names.push(ident);
     }
 
     Ok((
@@ -289,6 +778,160 @@ pub fn gen_par_fields(
     ))
 }
 
+/// Returns the code serializing `value`, a reference to the field's value, converted to
+/// the type given by `#[binja(into = "Type")]` if any.
+fn gen_ser_value(attrs: &FieldAttributes, value: TokenStream) -> TokenStream {
+    match attrs.into_type() {
+        Some(ty) => quote! {{
+            let value: #ty = ::core::convert::Into::into(::core::clone::Clone::clone(#value));
+            ::binja::BinarySerialize::binary_serialize(&value, serializer)
+        }},
+        None => quote! {
+            ::binja::BinarySerialize::binary_serialize(#value, serializer)
+        },
+    }
+}
+
+/// Wraps `code` so it runs with the integer encoding and endianness requested by the
+/// field's `#[binja(varint)]`, `#[binja(fixed)]`, `#[binja(big_endian)]` or
+/// `#[binja(little_endian)]` attributes, if any.
+///
+/// `target` is the serializer or parser variable `code` uses.
+fn with_field_config(
+    attrs: &FieldAttributes,
+    target: TokenStream,
+    code: TokenStream,
+) -> TokenStream {
+    with_config(&attrs.config_builders(), target, code)
+}
+
+/// Wraps `code` so it runs with the configuration of `target` changed by the `builders`
+/// methods, restoring it afterwards.
+pub fn with_config(
+    builders: &[TokenStream],
+    target: TokenStream,
+    code: TokenStream,
+) -> TokenStream {
+    if builders.is_empty() {
+        return code;
+    }
+
+    quote! {{
+        let config = #target.config()#(.#builders())*;
+        #target.scoped_config(config, |#target| #code)
+    }}
+}
+
+/// Returns the fields whose value is filled in when serializing, because a later field
+/// uses them as its `count`, along with that later field.
+///
+/// Only a count naming a field is filled in, more complex ones are checked instead.
+fn get_filled_counts(
+    fields: &syn::punctuated::Punctuated<syn::Field, syn::Token![,]>,
+) -> syn::Result<Vec<(syn::Ident, syn::Ident)>> {
+    let mut filled = Vec::new();
+
+    for (i, f) in fields.iter().enumerate() {
+        let attrs = FieldAttributes::parse(f)?;
+        let (Some(syn::Expr::Path(count)), Some(ident)) = (attrs.count(), &f.ident) else {
+            continue;
+        };
+        let Some(count) = count.path.get_ident() else {
+            continue;
+        };
+
+        let source = fields
+            .iter()
+            .take(i)
+            .find(|f| f.ident.as_ref() == Some(count));
+        if let Some(source) = source {
+            let source_attrs = FieldAttributes::parse(source)?;
+            if !source_attrs.skip()
+                && source_attrs.bits().is_none()
+                && source_attrs.len_of().is_none()
+                && source_attrs.constant().is_none()
+                && source_attrs.into_type().is_none()
+                && source_attrs.serialize_with().is_none()
+                && !filled.iter().any(|(filled, _)| filled == count)
+            {
+                filled.push((count.clone(), ident.clone()));
+            }
+        }
+    }
+
+    Ok(filled)
+}
+
+/// Checks that the target of the `len_of` attribute of field `i` is a field written
+/// after it, as a whole, and that no earlier field holds its length already.
+fn check_len_of_target(
+    fields: &syn::punctuated::Punctuated<syn::Field, syn::Token![,]>,
+    i: usize,
+    target: &syn::Ident,
+) -> syn::Result<()> {
+    for (j, f) in fields.iter().enumerate().take(i) {
+        if FieldAttributes::parse(f)?.len_of() == Some(target) {
+            return Err(syn::Error::new(
+                target.span(),
+                format!(
+                    "`{target}` already has its length held by `{}`",
+                    get_field_name(f, j)
+                ),
+            ));
+        }
+    }
+
+    let Some(f) = fields
+        .iter()
+        .skip(i + 1)
+        .find(|f| f.ident.as_ref() == Some(target))
+    else {
+        return Err(syn::Error::new(
+            target.span(),
+            format!("len_of must name a field declared after this one, found `{target}`"),
+        ));
+    };
+
+    let attrs = FieldAttributes::parse(f)?;
+    if attrs.skip() || attrs.bits().is_some() || attrs.len_of().is_some() {
+        return Err(syn::Error::new(
+            target.span(),
+            "the field named by len_of cannot be skip, bits or len_of",
+        ));
+    }
+
+    Ok(())
+}
+
+/// Wraps `expr` so the fields parsed before it are in scope by reference, as they are
+/// when serializing.
+fn with_fields_in_scope(fields: &[TokenStream], expr: &syn::Expr) -> TokenStream {
+    quote! {{
+        #(
+            #[allow(unused_variables)]
+            let #fields = &#fields;
+        )*
+        #expr
+    }}
+}
+
+/// Returns the `PathSegment` naming the field in parse errors: its name, or its position
+/// for unnamed fields.
+fn get_field_path(f: &syn::Field, i: usize) -> TokenStream {
+    let name = get_field_name(f, i);
+
+    quote! { ::binja::error::PathSegment::Field(#name) }
+}
+
+/// Returns the name of the field in error messages: its name, or its position for
+/// unnamed fields.
+fn get_field_name(f: &syn::Field, i: usize) -> String {
+    match &f.ident {
+        Some(ident) => ident.to_string(),
+        None => i.to_string(),
+    }
+}
+
 pub fn get_field_expr(f: &syn::Field, i: usize) -> TokenStream {
     if let Some(ident) = &f.ident {
         return ident.clone().into_token_stream();
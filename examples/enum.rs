@@ -11,6 +11,7 @@ enum EnumTagged {
 #[derive(BinarySerialize, BinaryParse, PartialEq, Debug)]
 #[repr(u16)]
 #[binja(repr = "u8", untagged)]
+#[allow(dead_code)]
 enum EnumUntagged {
     Tuple(u32, u32),
     Unit = 10,
@@ -1,6 +1,7 @@
 use binja::{BinaryParse, BinarySerialize, to_bytes};
 
 #[derive(BinaryParse, PartialEq, Eq, Debug)]
+#[allow(dead_code)]
 struct Unit;
 
 #[derive(BinarySerialize, BinaryParse, PartialEq, Eq, Debug)]
@@ -5,19 +5,28 @@
 /// - `endianness_strategy`: Determines the byte order (endianness) for integers (see [`EndiannessStrategy`]).
 /// - `optional_strategy`: Specifies how optional values (`Option<T>`) are serialized and deserialized (see [`OptionalStrategy`]).
 /// - `container_size_strategy`: Defines the size type used for encoding the length of collections like arrays or vectors (see [`ContainerSizeStrategy`]).
+/// - `integer_encoding_strategy`: Determines whether integers are written with a fixed width or as varints (see [`IntegerEncodingStrategy`]).
 /// - `limit`: Sets an optional limit for serialization or deserialization operations.
+/// - `max_depth`: Sets an optional limit on how deeply containers, structs and enums may nest.
+/// - `trailing_bytes_strategy`: Determines whether input left after a complete parse is an error (see [`TrailingBytesStrategy`]).
 ///
 /// The `Config` struct provides a builder-like API to configure these strategies using methods like:
 /// - `with_big_endian` / `with_little_endian`
 /// - `with_tagged_optional` / `with_untagged_optional`
-/// - `with_container_size_as` / `with_container_size_length`
+/// - `with_container_size_as` / `with_container_size_length` / `with_varint_container_size`
+/// - `with_fixed_int_encoding` / `with_varint_encoding`
 /// - `with_limit` / `with_no_limit`
+/// - `with_max_depth` / `with_no_max_depth`
+/// - `with_allow_trailing_bytes` / `with_reject_trailing_bytes`
 #[derive(Debug, Default, Clone, Copy)]
 pub struct Config {
     pub endianness_strategy: EndiannessStrategy,
     pub optional_strategy: OptionalStrategy,
     pub container_length_strategy: ContainerLengthStrategy,
+    pub integer_encoding_strategy: IntegerEncodingStrategy,
     pub limit: Option<usize>,
+    pub max_depth: Option<usize>,
+    pub trailing_bytes_strategy: TrailingBytesStrategy,
 }
 
 impl Config {
@@ -48,7 +57,7 @@ impl Config {
     }
 
     pub fn with_container_size_as<T>(mut self) -> Self {
-        match std::mem::size_of::<T>() {
+        match core::mem::size_of::<T>() {
             1 => self.container_length_strategy = ContainerLengthStrategy::OneByte,
             2 => self.container_length_strategy = ContainerLengthStrategy::TwoBytes,
             4 => self.container_length_strategy = ContainerLengthStrategy::FourBytes,
@@ -71,6 +80,25 @@ impl Config {
         self
     }
 
+    /// Makes binja encode container lengths as unsigned LEB128 varints.
+    pub fn with_varint_container_size(mut self) -> Self {
+        self.container_length_strategy = ContainerLengthStrategy::Varint;
+        self
+    }
+
+    /// Makes binja serialize and deserialize integers with their full fixed width.
+    pub fn with_fixed_int_encoding(mut self) -> Self {
+        self.integer_encoding_strategy = IntegerEncodingStrategy::Fixed;
+        self
+    }
+
+    /// Makes binja serialize and deserialize integers wider than a byte as LEB128 varints,
+    /// zigzag-encoded for signed types.
+    pub fn with_varint_encoding(mut self) -> Self {
+        self.integer_encoding_strategy = IntegerEncodingStrategy::Varint;
+        self
+    }
+
     pub fn with_limit(mut self, limit: usize) -> Self {
         self.limit = Some(limit);
         self
@@ -80,6 +108,33 @@ impl Config {
         self.limit = None;
         self
     }
+
+    /// Makes binja fail with [`Error::DepthLimitExceeded`](crate::error::Error::DepthLimitExceeded)
+    /// when containers, structs and enums nest more than `max_depth` levels deep.
+    ///
+    /// This keeps hostile input from overflowing the stack when parsing recursive types.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    pub fn with_no_max_depth(mut self) -> Self {
+        self.max_depth = None;
+        self
+    }
+
+    /// Makes binja ignore input left after a complete parse.
+    pub fn with_allow_trailing_bytes(mut self) -> Self {
+        self.trailing_bytes_strategy = TrailingBytesStrategy::Allow;
+        self
+    }
+
+    /// Makes binja fail with [`Error::TrailingBytes`](crate::error::Error::TrailingBytes)
+    /// when input is left after a complete parse.
+    pub fn with_reject_trailing_bytes(mut self) -> Self {
+        self.trailing_bytes_strategy = TrailingBytesStrategy::Reject;
+        self
+    }
 }
 
 /// Represents the strategy for determining the byte order (endianness).
@@ -111,6 +166,12 @@ pub enum OptionalStrategy {
 
 /// This strategy determines how much space is allocated for encoding the size of collections
 /// like arrays or vectors during serialization and deserialization.
+///
+/// - `OneByte` to `SixteenBytes`: The length is a fixed-size integer, in the configured endianness.
+///   `FourBytes` is the default.
+/// - `Varint`: The length is an unsigned LEB128 varint, 7 bits per byte with the high bit set on
+///   every byte but the last (e.g., `0x05` for 5, `0xac 0x02` for 300). Overlong encodings and
+///   lengths that don't fit a `usize` are rejected when parsing.
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub enum ContainerLengthStrategy {
     OneByte,
@@ -119,4 +180,34 @@ pub enum ContainerLengthStrategy {
     FourBytes,
     EightBytes,
     SixteenBytes,
+    Varint,
+}
+
+/// Represents the strategy for encoding integers wider than a byte (`u16..u128`, `i16..i128`).
+///
+/// - `Fixed` (default): Integers take their full width, in the configured endianness.
+/// - `Varint`: Unsigned integers are LEB128 varints, so small values take fewer bytes
+///   (e.g., `0x05` for `5u64`). Signed integers are zigzag-encoded first, mapping
+///   `0, -1, 1, -2, ...` to `0, 1, 2, 3, ...`, so small negative values stay short too.
+///
+/// `u8`, `i8`, floats, and container lengths are not affected: lengths follow
+/// [`ContainerLengthStrategy`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum IntegerEncodingStrategy {
+    #[default]
+    Fixed,
+    Varint,
+}
+
+/// Represents the strategy for input left over once a value has been fully parsed.
+///
+/// - `Allow` (default): The leftover bytes are ignored; `from_bytes` reports how many there are.
+/// - `Reject`: Parsing fails with an error giving the offset and count of the leftover bytes.
+///
+/// Only in-memory parses can be checked: a reader may always have more data to give.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TrailingBytesStrategy {
+    #[default]
+    Allow,
+    Reject,
 }
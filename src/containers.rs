@@ -1,6 +1,11 @@
-use std::ops::{Deref, DerefMut};
+use core::ops::{Deref, DerefMut};
 
-use crate::{BinaryParse, BinarySerialize, BinarySerializer, error::Result};
+use crate::{
+    BinaryParse, BinaryParser, BinarySerialize, BinarySerializer, Sink, Source,
+    error::{PathSegment, Result},
+    par::parser::to_length,
+    ser::serializer::check_length,
+};
 
 // type alias for ShortSizeContainer
 pub type SizelessContainer<T> = FixedSizeContainer<T, 0>;
@@ -47,22 +52,36 @@ where
     for<'a> &'a T: IntoIterator<Item = &'a I>,
     I: BinarySerialize,
 {
-    fn binary_serialize(&self, serializer: &mut BinarySerializer) -> Result<()> {
+    fn binary_serialize<S: Sink>(&self, serializer: &mut BinarySerializer<S>) -> Result<()> {
         let len = (&self.0).into_iter().count();
-        match SIZE {
-            0 => {}
-            1 => serializer.u8(len as u8)?,
-            2 => serializer.u16(len as u16)?,
-            4 => serializer.u32(len as u32)?,
-            8 => serializer.u64(len as u64)?,
-            16 => serializer.u128(len as u128)?,
+        let max = match SIZE {
+            0 | 16 => u128::MAX,
+            1 => u8::MAX as u128,
+            2 => u16::MAX as u128,
+            4 => u32::MAX as u128,
+            8 => u64::MAX as u128,
             _ => panic!("Invalid size for FixedSizeContainer"),
-        }
+        };
+        check_length(len, max)?;
+
+        // the length always takes exactly SIZE bytes, even with varint integers
+        let config = serializer.config().with_fixed_int_encoding();
+        serializer.scoped_config(config, |serializer| match SIZE {
+            0 => Ok(()),
+            1 => serializer.u8(len as u8),
+            2 => serializer.u16(len as u16),
+            4 => serializer.u32(len as u32),
+            8 => serializer.u64(len as u64),
+            16 => serializer.u128(len as u128),
+            _ => panic!("Invalid size for FixedSizeContainer"),
+        })?;
 
-        for item in &self.0 {
-            item.binary_serialize(serializer)?;
-        }
-        Ok(())
+        serializer.nested(|serializer| {
+            for item in &self.0 {
+                item.binary_serialize(serializer)?;
+            }
+            Ok(())
+        })
     }
 }
 
@@ -71,32 +90,40 @@ where
     T: Default + Extend<T::Item> + IntoIterator,
     T::Item: BinaryParse,
 {
-    fn binary_parse(parser: &mut crate::BinaryParser) -> Result<Self> {
-        let len = match SIZE {
-            0 => 0,
-            1 => parser.u8()? as usize,
-            2 => parser.u16()? as usize,
-            4 => parser.u32()? as usize,
-            8 => parser.u64()? as usize,
-            16 => parser.u128()? as usize,
-            _ => panic!("Invalid size for FixedSizeContainer"),
-        };
+    fn binary_parse<'de, S: Source<'de>>(parser: &mut BinaryParser<'de, S>) -> Result<Self> {
+        let config = parser.config().with_fixed_int_encoding();
+        let len = parser.scoped_config(config, |parser| {
+            let len = match SIZE {
+                0 => 0,
+                1 => parser.u8()? as usize,
+                2 => parser.u16()? as usize,
+                4 => parser.u32()? as usize,
+                8 => to_length(parser.u64()? as u128)?,
+                16 => to_length(parser.u128()?)?,
+                _ => panic!("Invalid size for FixedSizeContainer"),
+            };
+            Ok(len)
+        })?;
 
-        let mut container = T::default();
+        parser.nested(|parser| {
+            let mut container = T::default();
 
-        if len == 0 {
-            while let Ok(item) = T::Item::binary_parse(parser) {
-                container.extend(std::iter::once(item));
+            if len == 0 {
+                // items are parsed until one fails, which is left unconsumed
+                while let Some(item) = parser.attempt(T::Item::binary_parse)? {
+                    container.extend(core::iter::once(item));
+                }
+            } else {
+                // items are added as they are parsed, so a bogus length can't
+                // trigger a huge allocation up front
+                for i in 0..len {
+                    let item = parser.with_path(PathSegment::Index(i), T::Item::binary_parse)?;
+                    container.extend(core::iter::once(item));
+                }
             }
-        } else {
-            container.extend(
-                (0..len)
-                    .map(|_| T::Item::binary_parse(parser))
-                    .collect::<Result<Vec<_>>>()?,
-            );
-        }
-
-        Ok(FixedSizeContainer(container))
+
+            Ok(FixedSizeContainer(container))
+        })
     }
 }
 
@@ -1,14 +1,21 @@
-use std::fmt::{self, Display};
+#[cfg(feature = "serde")]
+use alloc::string::ToString;
+use alloc::{boxed::Box, string::String, vec, vec::Vec};
+use core::fmt::{self, Display};
 
 use bytes::TryGetError;
 #[cfg(feature = "serde")]
 use serde::{de, ser};
 
 // A type alias for Result that uses the custom Error enum
-pub type Result<T> = std::result::Result<T, Error>;
+pub type Result<T> = core::result::Result<T, Error>;
 
 // Defining a custom Error enum to represent various error types
+//
+// Non exhaustive, as some variants only exist with some features (e.g. `Io` with `std`),
+// which must stay additive
 #[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
 pub enum Error {
     // A generic error message
     Message(String),
@@ -67,10 +74,129 @@ pub enum Error {
         value: String,
         max: String,
     },
+
+    // Error for LEB128 varints that are overlong or don't fit the target type
+    InvalidVarint {
+        bytes: Vec<u8>,
+    },
+
+    // Error for values nested deeper than the configured maximum depth
+    DepthLimitExceeded {
+        max_depth: usize,
+    },
+
+    // Error for input left over after a complete parse
+    TrailingBytes {
+        offset: usize,
+        count: usize,
+    },
+
+    // Error for a fixed-size output buffer that is too small
+    BufferFull {
+        capacity: usize,
+        required: usize,
+    },
+
+    // Error for a magic number or constant field not matching the input
+    MagicMismatch {
+        expected: Vec<u8>,
+        found: Vec<u8>,
+    },
+
+    // Error for padding bytes not holding the expected fill byte
+    InvalidPadding {
+        expected: u8,
+        found: Vec<u8>,
+    },
+
+    // Error for a parsed field rejected by its `assert` or `try_map` attribute
+    Validation {
+        field: String,
+        message: String,
+    },
+
+    // Error wrapped with where it happened: the path to the value being parsed
+    // (outermost first) and the input offset of the failure
+    Context {
+        path: Vec<PathSegment>,
+        offset: usize,
+        source: Box<Error>,
+    },
+
+    // Error raised by the underlying reader or writer
+    #[cfg(feature = "std")]
+    Io {
+        kind: std::io::ErrorKind,
+        message: String,
+    },
+}
+
+/// One step of the path to a value, as reported by [`Error::Context`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PathSegment {
+    /// A struct or enum being parsed.
+    Type(&'static str),
+    /// A named field, or the position of an unnamed one (e.g. `"0"`).
+    Field(&'static str),
+    /// An enum variant.
+    Variant(&'static str),
+    /// An element of a container.
+    Index(usize),
+}
+
+impl Error {
+    /// Wraps the error with one more, outer, path segment.
+    ///
+    /// `offset` is only recorded the first time, so it stays where the failure happened.
+    pub fn with_path(self, segment: PathSegment, offset: usize) -> Self {
+        match self {
+            Error::Context {
+                mut path,
+                offset,
+                source,
+            } => {
+                path.insert(0, segment);
+                Error::Context {
+                    path,
+                    offset,
+                    source,
+                }
+            }
+            error => Error::Context {
+                path: vec![segment],
+                offset,
+                source: Box::new(error),
+            },
+        }
+    }
+
+    /// Returns the error without any context wrapped around it.
+    ///
+    /// Errors from derived parsers and containers come wrapped in [`Error::Context`], so
+    /// this is what to match on to tell what went wrong:
+    ///
+    /// ```rust
+    /// use binja::{BinaryParse, error::Error, from_bytes};
+    ///
+    /// #[derive(BinaryParse, Debug)]
+    /// struct Header {
+    ///     version: u16,
+    /// }
+    ///
+    /// let error = from_bytes::<Header>(&[0x01]).unwrap_err();
+    /// assert!(matches!(error, Error::Context { .. }));
+    /// assert!(matches!(error.root_cause(), Error::NoEnoughData { .. }));
+    /// ```
+    pub fn root_cause(&self) -> &Error {
+        match self {
+            Error::Context { source, .. } => source.root_cause(),
+            error => error,
+        }
+    }
 }
 
 // Implementing the standard Error trait for the custom Error enum
-impl std::error::Error for Error {}
+impl core::error::Error for Error {}
 
 // Implementing the serde::ser::Error trait for serialization errors
 #[cfg(feature = "serde")]
@@ -145,6 +271,56 @@ impl Display for Error {
             Error::Overflow { value, max } => {
                 write!(formatter, "Overflow: value {value}, max {max}")
             }
+            Error::InvalidVarint { bytes } => {
+                write!(formatter, "Invalid varint: {bytes:02x?}")
+            }
+            Error::DepthLimitExceeded { max_depth } => {
+                write!(formatter, "Depth limit exceeded: max depth {max_depth}")
+            }
+            Error::TrailingBytes { offset, count } => {
+                write!(formatter, "Trailing bytes: {count} left at offset {offset}")
+            }
+            Error::BufferFull { capacity, required } => {
+                write!(
+                    formatter,
+                    "Buffer full: capacity {capacity}, required {required}"
+                )
+            }
+            Error::InvalidPadding { expected, found } => {
+                write!(
+                    formatter,
+                    "Invalid padding: expected {expected:#04x} bytes, found {found:02x?}"
+                )
+            }
+            Error::MagicMismatch { expected, found } => {
+                write!(
+                    formatter,
+                    "Magic mismatch: expected {expected:02x?}, found {found:02x?}"
+                )
+            }
+            Error::Validation { field, message } => {
+                write!(formatter, "Validation failed for `{field}`: {message}")
+            }
+            Error::Context {
+                path,
+                offset,
+                source,
+            } => {
+                for (i, segment) in path.iter().enumerate() {
+                    match segment {
+                        // only the outermost type is shown, the fields say the rest
+                        PathSegment::Type(name) if i == 0 => formatter.write_str(name)?,
+                        PathSegment::Type(_) => {}
+                        PathSegment::Field(name) if i == 0 => formatter.write_str(name)?,
+                        PathSegment::Field(name) => write!(formatter, ".{name}")?,
+                        PathSegment::Variant(name) => write!(formatter, "::{name}")?,
+                        PathSegment::Index(index) => write!(formatter, "[{index}]")?,
+                    }
+                }
+                write!(formatter, " at offset {offset:#x}: {source}")
+            }
+            #[cfg(feature = "std")]
+            Error::Io { kind, message } => write!(formatter, "I/O error ({kind}): {message}"),
         }
     }
 }
@@ -157,3 +333,13 @@ impl From<TryGetError> for Error {
         }
     }
 }
+
+#[cfg(feature = "std")]
+impl From<std::io::Error> for Error {
+    fn from(value: std::io::Error) -> Self {
+        Error::Io {
+            kind: value.kind(),
+            message: value.to_string(),
+        }
+    }
+}
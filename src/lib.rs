@@ -1,3 +1,7 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
 mod par;
 mod ser;
 
@@ -5,8 +9,22 @@ pub mod config;
 pub mod containers;
 pub mod error;
 
-pub use par::{BinaryParse, parser::BinaryParser};
-pub use ser::{BinarySerialize, serializer::BinarySerializer};
+pub use par::{
+    BinaryParse,
+    parser::{BinaryParser, Checkpoint, TakeSource},
+    source::{SliceSource, Source},
+};
+pub use ser::{
+    BinarySerialize,
+    fixed_size::FixedEncodedSize,
+    serializer::{BinarySerializer, Reservation},
+    sink::{Sink, SizeSink, SliceSink},
+};
+
+#[cfg(feature = "std")]
+pub use par::source::IoSource;
+#[cfg(feature = "std")]
+pub use ser::sink::IoSink;
 
 use crate::error::Result;
 use bytes::BytesMut;
@@ -122,6 +140,206 @@ where
     Ok(ser.output())
 }
 
+/// Serializes a given value directly into an [`std::io::Write`] using the default configuration.
+///
+/// Unlike [`to_bytes`], the serialized bytes are streamed into `writer` as they are produced
+/// instead of being accumulated in memory. The writer is not flushed.
+///
+/// # Parameters
+/// - `value`: A reference to the value to be serialized. The value must implement the `Serialize` trait.
+/// - `writer`: The destination of the serialized bytes.
+///
+/// # Returns
+/// - `Ok(())`: The value was fully written.
+/// - `Err(Error)`: An error if serialization fails, exceeds the configured limit or the writer fails.
+///
+/// # Example
+/// ```rust
+/// use binja::{to_writer, BinarySerialize};
+///
+/// #[derive(BinarySerialize)]
+/// struct Example {
+///     field1: u32,
+///     field2: Option<u32>,
+/// }
+///
+/// let value = Example {
+///     field1: 42,
+///     field2: Some(7),
+/// };
+///
+/// let mut output = Vec::new();
+/// to_writer(&value, &mut output).unwrap();
+/// assert_eq!(output, vec![0x2A, 0x0, 0x0, 0x0, 0x1, 0x7, 0x0, 0x0, 0x0]);
+/// ```
+#[cfg(feature = "std")]
+pub fn to_writer<T, W>(value: &T, writer: W) -> Result<()>
+where
+    T: BinarySerialize,
+    W: std::io::Write,
+{
+    to_writer_with_config(value, writer, Config::default())
+}
+
+/// See [`to_writer`].
+#[cfg(all(feature = "serde", feature = "std"))]
+pub fn serde_to_writer<T, W>(value: &T, writer: W) -> Result<()>
+where
+    T: Serialize,
+    W: std::io::Write,
+{
+    serde_to_writer_with_config(value, writer, Config::default())
+}
+
+/// Serializes a given value directly into an [`std::io::Write`] using a custom configuration.
+///
+/// See [`to_writer`] and [`to_bytes_with_config`].
+#[cfg(feature = "std")]
+pub fn to_writer_with_config<T, W>(value: &T, writer: W, config: Config) -> Result<()>
+where
+    T: BinarySerialize,
+    W: std::io::Write,
+{
+    let mut serializer = BinarySerializer::with_sink(IoSink::new(writer), config);
+    value.binary_serialize(&mut serializer)?;
+    serializer.into_sink().finish()
+}
+
+/// See [`to_writer_with_config`].
+#[cfg(all(feature = "serde", feature = "std"))]
+pub fn serde_to_writer_with_config<T, W>(value: &T, writer: W, config: Config) -> Result<()>
+where
+    T: Serialize,
+    W: std::io::Write,
+{
+    let mut ser = BinarySerializer::with_sink(IoSink::new(writer), config);
+    value.serialize(&mut ser)?;
+    ser.into_sink().finish()
+}
+
+/// Serializes a given value into a caller-provided buffer using the default configuration.
+///
+/// Nothing is allocated: the value is written from the start of `buf`, and serialization
+/// fails with `Error::BufferFull` if it does not fit.
+///
+/// # Parameters
+/// - `value`: A reference to the value to be serialized. The value must implement the `Serialize` trait.
+/// - `buf`: The buffer receiving the serialized bytes.
+///
+/// # Returns
+/// - `Ok(usize)`: The number of bytes written to `buf`.
+/// - `Err(Error)`: An error if serialization fails, exceeds the configured limit or `buf` is too small.
+///
+/// # Example
+/// ```rust
+/// use binja::{to_slice, BinarySerialize};
+///
+/// #[derive(BinarySerialize)]
+/// struct Example {
+///     field1: u32,
+///     field2: Option<u32>,
+/// }
+///
+/// let value = Example {
+///     field1: 42,
+///     field2: Some(7),
+/// };
+///
+/// let mut buf = [0u8; 16];
+/// let size = to_slice(&value, &mut buf).unwrap();
+/// assert_eq!(buf[..size], [0x2A, 0x0, 0x0, 0x0, 0x1, 0x7, 0x0, 0x0, 0x0]);
+/// ```
+pub fn to_slice<T>(value: &T, buf: &mut [u8]) -> Result<usize>
+where
+    T: BinarySerialize,
+{
+    to_slice_with_config(value, buf, Config::default())
+}
+
+/// See [`to_slice`].
+#[cfg(feature = "serde")]
+pub fn serde_to_slice<T>(value: &T, buf: &mut [u8]) -> Result<usize>
+where
+    T: Serialize,
+{
+    serde_to_slice_with_config(value, buf, Config::default())
+}
+
+/// Serializes a given value into a caller-provided buffer using a custom configuration.
+///
+/// See [`to_slice`] and [`to_bytes_with_config`].
+pub fn to_slice_with_config<T>(value: &T, buf: &mut [u8], config: Config) -> Result<usize>
+where
+    T: BinarySerialize,
+{
+    let mut serializer = BinarySerializer::with_sink(SliceSink::new(buf), config);
+    value.binary_serialize(&mut serializer)?;
+    Ok(serializer.size())
+}
+
+/// See [`to_slice_with_config`].
+#[cfg(feature = "serde")]
+pub fn serde_to_slice_with_config<T>(value: &T, buf: &mut [u8], config: Config) -> Result<usize>
+where
+    T: Serialize,
+{
+    let mut ser = BinarySerializer::with_sink(SliceSink::new(buf), config);
+    value.serialize(&mut ser)?;
+    Ok(ser.size())
+}
+
+/// Computes the number of bytes a value serializes to, without serializing it anywhere.
+///
+/// The result matches the length of [`to_bytes_with_config`] with the same configuration,
+/// which makes it suitable to preallocate buffers or fill length headers.
+///
+/// # Parameters
+/// - `value`: A reference to the value to be measured. The value must implement the `Serialize` trait.
+/// - `config`: The `Config` the value would be serialized with.
+///
+/// # Returns
+/// - `Ok(usize)`: The exact serialized size in bytes.
+/// - `Err(Error)`: An error if serialization fails or exceeds the configured limit.
+///
+/// # Example
+/// ```rust
+/// use binja::{serialized_size, BinarySerialize};
+/// use binja::config::Config;
+///
+/// #[derive(BinarySerialize)]
+/// struct Example {
+///     field1: u32,
+///     field2: Option<u32>,
+/// }
+///
+/// let value = Example {
+///     field1: 42,
+///     field2: None,
+/// };
+///
+/// assert_eq!(serialized_size(&value, Config::default()).unwrap(), 5);
+/// assert_eq!(serialized_size(&value, Config::default().with_untagged_optional()).unwrap(), 4);
+/// ```
+pub fn serialized_size<T>(value: &T, config: Config) -> Result<usize>
+where
+    T: BinarySerialize,
+{
+    let mut serializer = BinarySerializer::with_sink(SizeSink::new(), config);
+    value.binary_serialize(&mut serializer)?;
+    Ok(serializer.size())
+}
+
+/// See [`serialized_size`].
+#[cfg(feature = "serde")]
+pub fn serde_serialized_size<T>(value: &T, config: Config) -> Result<usize>
+where
+    T: Serialize,
+{
+    let mut ser = BinarySerializer::with_sink(SizeSink::new(), config);
+    value.serialize(&mut ser)?;
+    Ok(ser.size())
+}
+
 /// Deserializes a binary slice into a value of type `T` using the default configuration.
 ///
 /// # Default Configuration
@@ -129,13 +347,18 @@ where
 /// - **Endianness**: Little-endian
 /// - **Limit**: No size limit
 /// - **Container Length**: 4 bytes (used to decode the length of sequences, strings, etc.)
+/// - **Trailing Bytes**: Allowed (`TrailingBytesStrategy::Allow`)
 ///
 /// # Parameters
 /// - `bytes`: The binary slice to deserialize. Must represent a valid serialized value of type `T`.
 ///
 /// # Returns
-/// - `Ok((T, usize))`: The deserialized value and the number of bytes read.
-/// - `Err(Error)`: If deserialization fails or the input is invalid.
+/// - `Ok((T, usize))`: The deserialized value and the number of bytes left unread after it
+///   (see [`from_bytes_prefix`] to get the number of bytes consumed instead).
+/// - `Err(Error)`: If deserialization fails or the input is invalid. With
+///   `TrailingBytesStrategy::Reject` (see [`from_bytes_with_config`] and
+///   [`from_bytes_exact`]), also `Error::TrailingBytes` with the offset and count of the
+///   leftover bytes if the value doesn't span the whole input.
 ///
 /// # Example
 /// ```rust
@@ -181,8 +404,10 @@ where
 /// - `config`: The `Config` specifying deserialization settings (endianness, optional strategy, etc.).
 ///
 /// # Returns
-/// - `Ok((T, usize))`: The deserialized value and the number of bytes read.
-/// - `Err(Error)`: If deserialization fails or the input is invalid.
+/// - `Ok((T, usize))`: The deserialized value and the number of bytes left unread after it
+///   (see [`from_bytes_prefix`] to get the number of bytes consumed instead).
+/// - `Err(Error)`: If deserialization fails or the input is invalid, or if bytes are left
+///   unread and the config rejects trailing bytes (`Error::TrailingBytes`).
 ///
 /// # Example
 /// ```rust
@@ -219,6 +444,7 @@ where
     let mut deserializer = BinaryParser::new(bytes, config);
 
     let v = T::binary_parse(&mut deserializer)?;
+    deserializer.check_trailing_bytes()?;
     let size = deserializer.size();
 
     Ok((v, size))
@@ -233,11 +459,368 @@ where
     let mut deserializer = BinaryParser::new(bytes, config);
 
     let v = T::deserialize(&mut deserializer)?;
+    deserializer.check_trailing_bytes()?;
     let size = deserializer.size();
 
     Ok((v, size))
 }
 
+/// Deserializes a binary slice that must hold exactly one value of type `T`, using the
+/// default configuration.
+///
+/// # Parameters
+/// - `bytes`: The binary slice to deserialize. Must contain nothing but a serialized `T`.
+///
+/// # Returns
+/// - `Ok(T)`: The deserialized value.
+/// - `Err(Error)`: If deserialization fails, or `Error::TrailingBytes` with the offset and count
+///   of the leftover bytes if the value doesn't span the whole input.
+///
+/// # Example
+/// ```rust
+/// use binja::{from_bytes_exact, error::Error};
+///
+/// let value: u16 = from_bytes_exact(&[0x2A, 0x00]).unwrap();
+/// assert_eq!(value, 42);
+///
+/// let result = from_bytes_exact::<u16>(&[0x2A, 0x00, 0xFF]);
+/// assert_eq!(result, Err(Error::TrailingBytes { offset: 2, count: 1 }));
+/// ```
+pub fn from_bytes_exact<T>(bytes: &[u8]) -> Result<T>
+where
+    T: BinaryParse,
+{
+    from_bytes_exact_with_config(bytes, Config::default())
+}
+
+/// See [`from_bytes_exact`].
+#[cfg(feature = "serde")]
+pub fn serde_from_bytes_exact<'a, T>(bytes: &'a [u8]) -> Result<T>
+where
+    T: Deserialize<'a>,
+{
+    serde_from_bytes_exact_with_config(bytes, Config::default())
+}
+
+/// Deserializes a binary slice that must hold exactly one value of type `T`, using a custom
+/// configuration. Trailing bytes are rejected whatever the configured strategy.
+///
+/// See [`from_bytes_exact`].
+pub fn from_bytes_exact_with_config<T>(bytes: &[u8], config: Config) -> Result<T>
+where
+    T: BinaryParse,
+{
+    let config = config.with_reject_trailing_bytes();
+    from_bytes_with_config(bytes, config).map(|(v, _)| v)
+}
+
+/// See [`from_bytes_exact_with_config`].
+#[cfg(feature = "serde")]
+pub fn serde_from_bytes_exact_with_config<'a, T>(bytes: &'a [u8], config: Config) -> Result<T>
+where
+    T: Deserialize<'a>,
+{
+    let config = config.with_reject_trailing_bytes();
+    serde_from_bytes_with_config(bytes, config).map(|(v, _)| v)
+}
+
+/// Deserializes a value of type `T` from the start of a binary slice using the default
+/// configuration, ignoring whatever follows it.
+///
+/// This is meant for inputs holding several values back to back: the returned count is
+/// where the next value starts.
+///
+/// # Parameters
+/// - `bytes`: The binary slice to deserialize. Must start with a serialized `T`.
+///
+/// # Returns
+/// - `Ok((T, usize))`: The deserialized value and the number of bytes it was parsed from.
+/// - `Err(Error)`: If deserialization fails or the input is invalid.
+///
+/// # Example
+/// ```rust
+/// use binja::from_bytes_prefix;
+///
+/// let bytes = [0x2A, 0x00, 0x07, 0x00];
+/// let (first, consumed): (u16, usize) = from_bytes_prefix(&bytes).unwrap();
+/// let (second, _): (u16, usize) = from_bytes_prefix(&bytes[consumed..]).unwrap();
+/// assert_eq!((first, second, consumed), (42, 7, 2));
+/// ```
+pub fn from_bytes_prefix<T>(bytes: &[u8]) -> Result<(T, usize)>
+where
+    T: BinaryParse,
+{
+    from_bytes_prefix_with_config(bytes, Config::default())
+}
+
+/// See [`from_bytes_prefix`].
+#[cfg(feature = "serde")]
+pub fn serde_from_bytes_prefix<'a, T>(bytes: &'a [u8]) -> Result<(T, usize)>
+where
+    T: Deserialize<'a>,
+{
+    serde_from_bytes_prefix_with_config(bytes, Config::default())
+}
+
+/// Deserializes a value of type `T` from the start of a binary slice using a custom
+/// configuration. Trailing bytes are allowed whatever the configured strategy.
+///
+/// See [`from_bytes_prefix`].
+pub fn from_bytes_prefix_with_config<T>(bytes: &[u8], config: Config) -> Result<(T, usize)>
+where
+    T: BinaryParse,
+{
+    let mut parser = BinaryParser::new(bytes, config);
+
+    let v = T::binary_parse(&mut parser)?;
+
+    Ok((v, parser.position()))
+}
+
+/// See [`from_bytes_prefix_with_config`].
+#[cfg(feature = "serde")]
+pub fn serde_from_bytes_prefix_with_config<'a, T>(
+    bytes: &'a [u8],
+    config: Config,
+) -> Result<(T, usize)>
+where
+    T: Deserialize<'a>,
+{
+    let mut parser = BinaryParser::new(bytes, config);
+
+    let v = T::deserialize(&mut parser)?;
+
+    Ok((v, parser.position()))
+}
+
+/// Parses a value of type `T` from an [`std::io::Read`] using the default configuration.
+///
+/// Bytes are pulled from `reader` as the parse requests them, so the input never has to be
+/// fully loaded in memory and the reader is left positioned right after the parsed value.
+/// Strings and byte buffers are copied out of the reader.
+///
+/// # Parameters
+/// - `reader`: The source of the serialized bytes.
+///
+/// # Returns
+/// - `Ok(T)`: The parsed value.
+/// - `Err(Error)`: If parsing fails, the input ends early (`Error::NoEnoughData`) or the reader fails.
+///
+/// # Example
+/// ```rust
+/// use binja::{from_reader, BinaryParse};
+///
+/// #[derive(BinaryParse, PartialEq, Debug)]
+/// struct Example {
+///     field1: u32,
+///     field2: Option<u32>,
+/// }
+///
+/// let bytes = vec![0x2A, 0x0, 0x0, 0x0, 0x1, 0x7, 0x0, 0x0, 0x0];
+/// let value: Example = from_reader(bytes.as_slice()).unwrap();
+/// assert_eq!(
+///     value,
+///     Example {
+///         field1: 42,
+///         field2: Some(7),
+///     }
+/// );
+/// ```
+#[cfg(feature = "std")]
+pub fn from_reader<T, R>(reader: R) -> Result<T>
+where
+    T: BinaryParse,
+    R: std::io::Read,
+{
+    from_reader_with_config(reader, Config::default())
+}
+
+/// Parses a value of type `T` from an [`std::io::Read`] using a custom configuration.
+///
+/// The trailing bytes strategy doesn't apply: whatever follows the value is left in the reader.
+///
+/// See [`from_reader`] and [`from_bytes_with_config`].
+#[cfg(feature = "std")]
+pub fn from_reader_with_config<T, R>(reader: R, config: Config) -> Result<T>
+where
+    T: BinaryParse,
+    R: std::io::Read,
+{
+    let mut parser = BinaryParser::with_source(IoSource::new(reader), config);
+    T::binary_parse(&mut parser)
+}
+
+// Items used by the code generated by `binja_derive`, not part of the public API.
+#[doc(hidden)]
+pub mod __private {
+    pub use alloc::{format, string::String};
+
+    use alloc::vec::Vec;
+
+    use crate::{
+        BinaryParse, BinaryParser, BinarySerialize, BinarySerializer, Sink, Source,
+        error::{Error, PathSegment, Result},
+    };
+
+    /// Converts the value of a length expression (e.g. `#[binja(size = "len")]`) to a `usize`.
+    ///
+    /// Expressions see the other fields by reference, so references are accepted as well.
+    pub trait IntoLength {
+        fn into_length(self) -> Result<usize>;
+    }
+
+    /// Converts a length computed while serializing (e.g. `#[binja(len_of = "body")]`)
+    /// to the type of the field holding it.
+    pub trait FromLength: Sized {
+        fn from_length(len: usize) -> Result<Self>;
+    }
+
+    macro_rules! impl_length {
+        ($($ty:ty),*) => {$(
+            impl FromLength for $ty {
+                fn from_length(len: usize) -> Result<Self> {
+                    Self::try_from(len).map_err(|_| Error::Overflow {
+                        value: format!("{len}"),
+                        max: format!("{}", Self::MAX),
+                    })
+                }
+            }
+
+            impl IntoLength for $ty {
+                fn into_length(self) -> Result<usize> {
+                    usize::try_from(self).map_err(|_| Error::Overflow {
+                        value: format!("{self}"),
+                        max: format!("{}", usize::MAX),
+                    })
+                }
+            }
+
+            impl IntoLength for &$ty {
+                fn into_length(self) -> Result<usize> {
+                    (*self).into_length()
+                }
+            }
+        )*};
+    }
+
+    impl_length!(
+        u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize
+    );
+
+    /// A [`Sink`] checking the bytes written are the next ones of a parser's input.
+    struct ExpectSink<'a, 'de, S> {
+        parser: &'a mut BinaryParser<'de, S>,
+    }
+
+    impl<'de, S> Sink for ExpectSink<'_, 'de, S>
+    where
+        S: Source<'de>,
+    {
+        fn write(&mut self, bytes: &[u8]) -> Result<()> {
+            self.parser.expect_bytes(bytes)
+        }
+    }
+
+    /// Parses a `#[binja(const = ...)]` field, checking the input holds `value` as it
+    /// would be serialized with the parser's configuration at the parser's position.
+    ///
+    /// On a mismatch, the parser is left where the field started, so that is the offset
+    /// reported.
+    pub fn expect_const<'de, S, T>(parser: &mut BinaryParser<'de, S>, value: &T) -> Result<()>
+    where
+        S: Source<'de>,
+        T: BinarySerialize + ?Sized,
+    {
+        let config = parser.config().with_no_limit();
+        let position = parser.position();
+        let checkpoint = parser.checkpoint();
+        let sink = ExpectSink {
+            parser: &mut *parser,
+        };
+        let mut serializer = BinarySerializer::with_sink_at(sink, config, position);
+        match value.binary_serialize(&mut serializer) {
+            Ok(()) => {
+                parser.commit(checkpoint);
+                Ok(())
+            }
+            Err(error) => {
+                parser.restore(checkpoint)?;
+                Err(error)
+            }
+        }
+    }
+
+    /// Compares two byte string tags of an enum (e.g. `#[binja(tag = b"PNG")]`) at compile
+    /// time, as `==` isn't usable on arrays in constants.
+    pub const fn tags_eq(a: &[u8], b: &[u8]) -> bool {
+        if a.len() != b.len() {
+            return false;
+        }
+        let mut i = 0;
+        while i < a.len() {
+            if a[i] != b[i] {
+                return false;
+            }
+            i += 1;
+        }
+        true
+    }
+
+    /// Containers a `#[binja(count = "n")]` field can be, written without a length prefix.
+    pub trait SerializeCounted {
+        fn counted_len(&self) -> usize;
+
+        fn serialize_counted<S: Sink>(&self, serializer: &mut BinarySerializer<S>) -> Result<()>;
+    }
+
+    /// Containers a `#[binja(count = "n")]` field can be, read without a length prefix.
+    pub trait ParseCounted: Sized {
+        fn parse_counted<'de, S: Source<'de>>(
+            parser: &mut BinaryParser<'de, S>,
+            count: usize,
+        ) -> Result<Self>;
+    }
+
+    impl<T> SerializeCounted for Vec<T>
+    where
+        T: BinarySerialize,
+    {
+        fn counted_len(&self) -> usize {
+            self.len()
+        }
+
+        fn serialize_counted<S: Sink>(&self, serializer: &mut BinarySerializer<S>) -> Result<()> {
+            serializer.nested(|serializer| {
+                for item in self {
+                    item.binary_serialize(serializer)?;
+                }
+
+                Ok(())
+            })
+        }
+    }
+
+    impl<T> ParseCounted for Vec<T>
+    where
+        T: BinaryParse,
+    {
+        fn parse_counted<'de, S: Source<'de>>(
+            parser: &mut BinaryParser<'de, S>,
+            count: usize,
+        ) -> Result<Self> {
+            parser.nested(|parser| {
+                let mut vec = Vec::with_capacity(parser.capacity_hint::<T>(count));
+
+                for i in 0..count {
+                    vec.push(parser.with_path(PathSegment::Index(i), T::binary_parse)?);
+                }
+
+                Ok(vec)
+            })
+        }
+    }
+}
+
 #[macro_export]
 macro_rules! bit {
     ($bits:expr) => {
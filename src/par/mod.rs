@@ -1,115 +1,122 @@
+use alloc::{boxed::Box, string::String, vec::Vec};
+
 use parser::BinaryParser;
+use source::Source;
 
 use crate::{
     config::OptionalStrategy,
-    error::{Error, Result},
+    error::{Error, PathSegment, Result},
 };
 #[cfg(feature = "serde")]
 mod serde_impl;
 
 pub mod parser;
+pub mod source;
 
 pub trait BinaryParse: Sized {
-    fn binary_parse(parser: &mut BinaryParser) -> Result<Self>;
+    fn binary_parse<'de, S: Source<'de>>(parser: &mut BinaryParser<'de, S>) -> Result<Self>;
 
-    fn binary_parse_mut(&mut self, parser: &mut BinaryParser) -> Result<()> {
+    fn binary_parse_mut<'de, S: Source<'de>>(
+        &mut self,
+        parser: &mut BinaryParser<'de, S>,
+    ) -> Result<()> {
         *self = Self::binary_parse(parser)?;
         Ok(())
     }
 }
 
 impl BinaryParse for () {
-    fn binary_parse(_parser: &mut BinaryParser) -> Result<Self> {
+    fn binary_parse<'de, S: Source<'de>>(_parser: &mut BinaryParser<'de, S>) -> Result<Self> {
         Ok(())
     }
 }
 
 impl BinaryParse for bool {
-    fn binary_parse(parser: &mut BinaryParser) -> Result<Self> {
+    fn binary_parse<'de, S: Source<'de>>(parser: &mut BinaryParser<'de, S>) -> Result<Self> {
         parser.bool()
     }
 }
 
 impl BinaryParse for i8 {
-    fn binary_parse(parser: &mut BinaryParser) -> Result<Self> {
+    fn binary_parse<'de, S: Source<'de>>(parser: &mut BinaryParser<'de, S>) -> Result<Self> {
         parser.i8()
     }
 }
 
 impl BinaryParse for i16 {
-    fn binary_parse(parser: &mut BinaryParser) -> Result<Self> {
+    fn binary_parse<'de, S: Source<'de>>(parser: &mut BinaryParser<'de, S>) -> Result<Self> {
         parser.i16()
     }
 }
 
 impl BinaryParse for i32 {
-    fn binary_parse(parser: &mut BinaryParser) -> Result<Self> {
+    fn binary_parse<'de, S: Source<'de>>(parser: &mut BinaryParser<'de, S>) -> Result<Self> {
         parser.i32()
     }
 }
 
 impl BinaryParse for i64 {
-    fn binary_parse(parser: &mut BinaryParser) -> Result<Self> {
+    fn binary_parse<'de, S: Source<'de>>(parser: &mut BinaryParser<'de, S>) -> Result<Self> {
         parser.i64()
     }
 }
 
 impl BinaryParse for i128 {
-    fn binary_parse(parser: &mut BinaryParser) -> Result<Self> {
+    fn binary_parse<'de, S: Source<'de>>(parser: &mut BinaryParser<'de, S>) -> Result<Self> {
         parser.i128()
     }
 }
 
 impl BinaryParse for u8 {
-    fn binary_parse(parser: &mut BinaryParser) -> Result<Self> {
+    fn binary_parse<'de, S: Source<'de>>(parser: &mut BinaryParser<'de, S>) -> Result<Self> {
         parser.u8()
     }
 }
 
 impl BinaryParse for u16 {
-    fn binary_parse(parser: &mut BinaryParser) -> Result<Self> {
+    fn binary_parse<'de, S: Source<'de>>(parser: &mut BinaryParser<'de, S>) -> Result<Self> {
         parser.u16()
     }
 }
 
 impl BinaryParse for u32 {
-    fn binary_parse(parser: &mut BinaryParser) -> Result<Self> {
+    fn binary_parse<'de, S: Source<'de>>(parser: &mut BinaryParser<'de, S>) -> Result<Self> {
         parser.u32()
     }
 }
 
 impl BinaryParse for u64 {
-    fn binary_parse(parser: &mut BinaryParser) -> Result<Self> {
+    fn binary_parse<'de, S: Source<'de>>(parser: &mut BinaryParser<'de, S>) -> Result<Self> {
         parser.u64()
     }
 }
 impl BinaryParse for u128 {
-    fn binary_parse(parser: &mut BinaryParser) -> Result<Self> {
+    fn binary_parse<'de, S: Source<'de>>(parser: &mut BinaryParser<'de, S>) -> Result<Self> {
         parser.u128()
     }
 }
 impl BinaryParse for f32 {
-    fn binary_parse(parser: &mut BinaryParser) -> Result<Self> {
+    fn binary_parse<'de, S: Source<'de>>(parser: &mut BinaryParser<'de, S>) -> Result<Self> {
         parser.f32()
     }
 }
 
 impl BinaryParse for f64 {
-    fn binary_parse(parser: &mut BinaryParser) -> Result<Self> {
+    fn binary_parse<'de, S: Source<'de>>(parser: &mut BinaryParser<'de, S>) -> Result<Self> {
         parser.f64()
     }
 }
 
 impl BinaryParse for char {
-    fn binary_parse(parser: &mut BinaryParser) -> Result<Self> {
+    fn binary_parse<'de, S: Source<'de>>(parser: &mut BinaryParser<'de, S>) -> Result<Self> {
         parser.char()
     }
 }
 
 impl BinaryParse for String {
-    fn binary_parse(parser: &mut BinaryParser) -> Result<Self> {
+    fn binary_parse<'de, S: Source<'de>>(parser: &mut BinaryParser<'de, S>) -> Result<Self> {
         let str = parser.string()?;
-        Ok(str.to_string())
+        Ok(str.into_owned())
     }
 }
 
@@ -117,7 +124,7 @@ impl<T> BinaryParse for Option<T>
 where
     T: BinaryParse,
 {
-    fn binary_parse(parser: &mut BinaryParser) -> Result<Self>
+    fn binary_parse<'de, S: Source<'de>>(parser: &mut BinaryParser<'de, S>) -> Result<Self>
     where
         Self: Sized,
     {
@@ -126,10 +133,10 @@ where
                 true => Ok(Some(T::binary_parse(parser)?)),
                 false => Ok(None),
             },
-            OptionalStrategy::Untagged => match T::binary_parse(parser) {
-                Ok(v) => Ok(Some(v)),
-                Err(_) => Ok(None),
-            },
+            OptionalStrategy::Untagged => {
+                // a value that fails to parse is absent, and must not consume any input
+                parser.attempt(T::binary_parse)
+            }
         }
     }
 }
@@ -138,19 +145,21 @@ impl<T> BinaryParse for Vec<T>
 where
     T: BinaryParse,
 {
-    fn binary_parse(parser: &mut BinaryParser) -> Result<Self>
+    fn binary_parse<'de, S: Source<'de>>(parser: &mut BinaryParser<'de, S>) -> Result<Self>
     where
         Self: Sized,
     {
-        let len = parser.container_size()?;
+        parser.nested(|parser| {
+            let len = parser.container_size()?;
 
-        let mut vec = Vec::with_capacity(len);
+            let mut vec = Vec::with_capacity(parser.capacity_hint::<T>(len));
 
-        for _ in 0..len {
-            vec.push(T::binary_parse(parser)?);
-        }
+            for i in 0..len {
+                vec.push(parser.with_path(PathSegment::Index(i), T::binary_parse)?);
+            }
 
-        Ok(vec)
+            Ok(vec)
+        })
     }
 }
 
@@ -158,13 +167,13 @@ impl<T, const N: usize> BinaryParse for [T; N]
 where
     T: BinaryParse,
 {
-    fn binary_parse(parser: &mut BinaryParser) -> Result<Self>
+    fn binary_parse<'de, S: Source<'de>>(parser: &mut BinaryParser<'de, S>) -> Result<Self>
     where
         Self: Sized,
     {
         let mut vec = Vec::with_capacity(N);
-        for _ in 0..N {
-            vec.push(T::binary_parse(parser)?);
+        for i in 0..N {
+            vec.push(parser.with_path(PathSegment::Index(i), T::binary_parse)?);
         }
 
         let boxed_slice: Box<[T]> = vec.into_boxed_slice();
@@ -176,91 +185,104 @@ where
     }
 }
 
+#[cfg(feature = "std")]
 impl<K, V> BinaryParse for std::collections::HashMap<K, V>
 where
-    K: BinaryParse + std::hash::Hash + Eq,
+    K: BinaryParse + core::hash::Hash + Eq,
     V: BinaryParse,
 {
-    fn binary_parse(parser: &mut BinaryParser) -> Result<Self>
+    fn binary_parse<'de, S: Source<'de>>(parser: &mut BinaryParser<'de, S>) -> Result<Self>
     where
         Self: Sized,
     {
-        let len = parser.container_size()?;
+        parser.nested(|parser| {
+            let len = parser.container_size()?;
 
-        let mut map = std::collections::HashMap::with_capacity(len);
+            let mut map =
+                std::collections::HashMap::with_capacity(parser.capacity_hint::<(K, V)>(len));
 
-        for _ in 0..len {
-            let key = K::binary_parse(parser)?;
-            let value = V::binary_parse(parser)?;
-            map.insert(key, value);
-        }
+            for i in 0..len {
+                let (key, value) = parser.with_path(PathSegment::Index(i), |parser| {
+                    Ok((K::binary_parse(parser)?, V::binary_parse(parser)?))
+                })?;
+                map.insert(key, value);
+            }
 
-        Ok(map)
+            Ok(map)
+        })
     }
 }
 
+#[cfg(feature = "std")]
 impl<T> BinaryParse for std::collections::HashSet<T>
 where
-    T: BinaryParse + std::hash::Hash + Eq,
+    T: BinaryParse + core::hash::Hash + Eq,
 {
-    fn binary_parse(parser: &mut BinaryParser) -> Result<Self>
+    fn binary_parse<'de, S: Source<'de>>(parser: &mut BinaryParser<'de, S>) -> Result<Self>
     where
         Self: Sized,
     {
-        let len = parser.container_size()?;
+        parser.nested(|parser| {
+            let len = parser.container_size()?;
 
-        let mut set = std::collections::HashSet::with_capacity(len);
+            let mut set = std::collections::HashSet::with_capacity(parser.capacity_hint::<T>(len));
 
-        for _ in 0..len {
-            let value = T::binary_parse(parser)?;
-            set.insert(value);
-        }
+            for i in 0..len {
+                let value = parser.with_path(PathSegment::Index(i), T::binary_parse)?;
+                set.insert(value);
+            }
 
-        Ok(set)
+            Ok(set)
+        })
     }
 }
 
-impl<K, V> BinaryParse for std::collections::BTreeMap<K, V>
+impl<K, V> BinaryParse for alloc::collections::BTreeMap<K, V>
 where
-    K: BinaryParse + std::cmp::Ord,
+    K: BinaryParse + core::cmp::Ord,
     V: BinaryParse,
 {
-    fn binary_parse(parser: &mut BinaryParser) -> Result<Self>
+    fn binary_parse<'de, S: Source<'de>>(parser: &mut BinaryParser<'de, S>) -> Result<Self>
     where
         Self: Sized,
     {
-        let len = parser.container_size()?;
+        parser.nested(|parser| {
+            let len = parser.container_size()?;
 
-        let mut map = std::collections::BTreeMap::new();
+            let mut map = alloc::collections::BTreeMap::new();
 
-        for _ in 0..len {
-            let key = K::binary_parse(parser)?;
-            let value = V::binary_parse(parser)?;
-            map.insert(key, value);
-        }
+            for i in 0..len {
+                let (key, value) = parser.with_path(PathSegment::Index(i), |parser| {
+                    Ok((K::binary_parse(parser)?, V::binary_parse(parser)?))
+                })?;
+                map.insert(key, value);
+            }
 
-        Ok(map)
+            Ok(map)
+        })
     }
 }
 
-impl<T> BinaryParse for std::collections::BTreeSet<T>
+impl<T> BinaryParse for alloc::collections::BTreeSet<T>
 where
-    T: BinaryParse + std::cmp::Ord,
+    T: BinaryParse + core::cmp::Ord,
 {
-    fn binary_parse(parser: &mut BinaryParser) -> Result<Self>
+    fn binary_parse<'de, S: Source<'de>>(parser: &mut BinaryParser<'de, S>) -> Result<Self>
     where
         Self: Sized,
     {
-        let len = parser.container_size()?;
+        parser.nested(|parser| {
+            let len = parser.container_size()?;
 
-        let mut set = std::collections::BTreeSet::new();
+            let mut set = alloc::collections::BTreeSet::new();
 
-        for _ in 0..len {
-            let value = T::binary_parse(parser)?;
-            set.insert(value);
-        }
+            for i in 0..len {
+                let value = parser.with_path(PathSegment::Index(i), T::binary_parse)?;
+                set.insert(value);
+            }
 
-        Ok(set)
+            Ok(set)
+        })
     }
 }
 
@@ -270,7 +292,7 @@ macro_rules! impl_binary_parse_for_tuple {
         where
             $($name: BinaryParse,)*
         {
-            fn binary_parse(parser: &mut BinaryParser) -> Result<Self> {
+            fn binary_parse<'de, S: Source<'de>>(parser: &mut BinaryParser<'de, S>) -> Result<Self> {
                 Ok(($(
                     $name::binary_parse(parser)?,
                 )+))
@@ -1,38 +1,487 @@
-use bytes::Buf;
-
-use crate::config::{Config, ContainerLengthStrategy, EndiannessStrategy};
-use crate::error::{Error, Result};
-
-pub struct BinaryParser<'de> {
-    input: &'de [u8],
+use alloc::{
+    borrow::Cow,
+    string::{String, ToString},
+};
+use core::marker::PhantomData;
+
+use crate::config::{
+    Config, ContainerLengthStrategy, EndiannessStrategy, IntegerEncodingStrategy,
+    TrailingBytesStrategy,
+};
+use crate::error::{Error, PathSegment, Result};
+
+use super::{
+    BinaryParse,
+    source::{SliceSource, Source},
+};
+
+pub struct BinaryParser<'de, S = SliceSource<'de>> {
+    // Lifetime of the data that can be borrowed from the input
+    marker: PhantomData<&'de ()>,
     // Configuration for serialization (e.g., endianness, optional strategy, etc.)
     config: Config,
+    // Number of bytes consumed so far, checked against the configured limit
+    position: usize,
+    // Current nesting level of containers, structs and enums
+    depth: usize,
+    // Number of attempts in progress whose errors are discarded, see `attempt`
+    attempts: usize,
+    // Source the parsed bytes are pulled from
+    source: S,
+}
+
+/// Upper bound, in bytes, on what a container preallocates before its elements are parsed.
+const MAX_PREALLOCATION: usize = 1 << 20;
+
+/// A position in the input a [`BinaryParser`] can go back to.
+///
+/// Created by [`BinaryParser::checkpoint`], and given back to either
+/// [`BinaryParser::restore`] or [`BinaryParser::commit`].
+#[derive(Debug)]
+#[must_use = "a checkpoint must be restored or committed"]
+pub struct Checkpoint {
+    position: usize,
+}
+
+/// A [`Source`] over the next bytes of another parser's input, up to a fixed length.
+///
+/// Created by [`BinaryParser::take`]. Bytes are read through the parent parser, so its
+/// position and limit keep track of them, and reading past the end of the region fails
+/// with [`Error::NoEnoughData`].
+pub struct TakeSource<'a, 'de> {
+    // the parent's source type is erased, so regions nested in regions keep this type
+    parent: &'a mut dyn Parent<'de>,
+    // bytes of the region not consumed yet
+    remaining: usize,
+}
+
+/// The operations a [`TakeSource`] needs from the parser it reads through.
+trait Parent<'de> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<()>;
+    fn read_bytes(&mut self, len: usize) -> Result<Cow<'de, [u8]>>;
+    fn input(&self) -> &'de [u8];
+    fn remaining(&self) -> Option<usize>;
+    fn retain(&mut self);
+    fn release(&mut self);
+    fn rewind(&mut self, len: usize) -> Result<()>;
+}
+
+impl<'de, S> Parent<'de> for BinaryParser<'de, S>
+where
+    S: Source<'de>,
+{
+    fn read(&mut self, buf: &mut [u8]) -> Result<()> {
+        BinaryParser::read(self, buf)
+    }
+
+    fn read_bytes(&mut self, len: usize) -> Result<Cow<'de, [u8]>> {
+        BinaryParser::read_bytes(self, len)
+    }
+
+    fn input(&self) -> &'de [u8] {
+        BinaryParser::input(self)
+    }
+
+    fn remaining(&self) -> Option<usize> {
+        self.source.remaining()
+    }
+
+    fn retain(&mut self) {
+        self.source.retain()
+    }
+
+    fn release(&mut self) {
+        self.source.release()
+    }
+
+    fn rewind(&mut self, len: usize) -> Result<()> {
+        self.source.rewind(len)?;
+        self.position -= len;
+        Ok(())
+    }
+}
+
+impl TakeSource<'_, '_> {
+    fn check_region(&self, len: usize) -> Result<()> {
+        if len > self.remaining {
+            return Err(Error::NoEnoughData {
+                expected: len,
+                available: self.remaining,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl<'a, 'de> Source<'a> for TakeSource<'a, 'de> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<()> {
+        self.check_region(buf.len())?;
+        self.parent.read(buf)?;
+        self.remaining -= buf.len();
+        Ok(())
+    }
+
+    fn read_bytes(&mut self, len: usize) -> Result<Cow<'a, [u8]>> {
+        self.check_region(len)?;
+        let value = self.parent.read_bytes(len)?;
+        self.remaining -= len;
+        Ok(value)
+    }
+
+    fn input(&self) -> &'a [u8] {
+        let input = self.parent.input();
+        &input[..input.len().min(self.remaining)]
+    }
+
+    fn remaining(&self) -> Option<usize> {
+        match self.parent.remaining() {
+            Some(available) => Some(available.min(self.remaining)),
+            None => Some(self.remaining),
+        }
+    }
+
+    fn retain(&mut self) {
+        self.parent.retain()
+    }
+
+    fn release(&mut self) {
+        self.parent.release()
+    }
+
+    fn rewind(&mut self, len: usize) -> Result<()> {
+        self.parent.rewind(len)?;
+        self.remaining += len;
+        Ok(())
+    }
 }
 
 impl<'de> BinaryParser<'de> {
     pub fn new(input: &'de [u8], config: Config) -> Self {
-        Self { input, config }
+        Self::with_source(SliceSource::new(input), config)
     }
+}
 
-    pub fn input(&self) -> &'de [u8] {
-        self.input
+impl<'de, S> BinaryParser<'de, S>
+where
+    S: Source<'de>,
+{
+    /// Creates a new `BinaryParser` pulling its input from the given source.
+    pub fn with_source(source: S, config: Config) -> Self {
+        Self {
+            marker: PhantomData,
+            config,
+            position: 0,
+            depth: 0,
+            attempts: 0,
+            source,
+        }
+    }
+
+    /// Consumes the parser and returns the underlying source.
+    pub fn into_source(self) -> S {
+        self.source
+    }
+
+    /// Parses a value of type `T` without consuming it.
+    pub fn peek<T>(&mut self) -> Result<T>
+    where
+        T: BinaryParse,
+    {
+        let checkpoint = self.checkpoint();
+        let value = T::binary_parse(self);
+        self.restore(checkpoint)?;
+        value
     }
 
     pub fn config(&self) -> &Config {
         &self.config
     }
 
+    /// Runs `f` with `config` in place of the current configuration, restoring the
+    /// previous configuration afterwards, even if `f` fails.
+    ///
+    /// This is how a single value can be read with different settings than its
+    /// surroundings (e.g., a varint field inside a fixed-width struct).
+    pub fn scoped_config<R>(
+        &mut self,
+        config: Config,
+        f: impl FnOnce(&mut Self) -> Result<R>,
+    ) -> Result<R> {
+        let previous = core::mem::replace(&mut self.config, config);
+        let result = f(self);
+        self.config = previous;
+        result
+    }
+
+    /// Runs `f` one nesting level deeper, failing with [`Error::DepthLimitExceeded`]
+    /// if that goes past the configured maximum depth.
+    ///
+    /// Containers, structs and enums read their contents through this, so recursive
+    /// types can't nest without bound.
+    pub fn nested<R>(&mut self, f: impl FnOnce(&mut Self) -> Result<R>) -> Result<R> {
+        self.enter()?;
+        let result = f(self);
+        self.leave();
+        result
+    }
+
+    /// Goes one nesting level deeper, checking the configured maximum depth.
+    pub(crate) fn enter(&mut self) -> Result<()> {
+        if let Some(max_depth) = self.config.max_depth
+            && self.depth >= max_depth
+        {
+            return Err(Error::DepthLimitExceeded { max_depth });
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    /// Goes back up one nesting level.
+    pub(crate) fn leave(&mut self) {
+        self.depth -= 1;
+    }
+
+    /// Runs `f`, wrapping any error it returns with `segment` and the offset it failed at.
+    ///
+    /// Derived parsers wrap every field this way, and containers every element, so a
+    /// failure deep in a value reports its full path (e.g. `Packet.payload[3].crc`).
+    pub fn with_path<R>(
+        &mut self,
+        segment: PathSegment,
+        f: impl FnOnce(&mut Self) -> Result<R>,
+    ) -> Result<R> {
+        match f(self) {
+            Ok(value) => Ok(value),
+            // the error of an attempt is discarded, so its context is not worth building
+            Err(error) if self.attempts > 0 => Err(error),
+            Err(error) => Err(error.with_path(segment, self.position)),
+        }
+    }
+
+    /// Tries to parse with `f`, going back to where it started and returning `None` if
+    /// it fails.
+    ///
+    /// The error is discarded, so no [`Error::Context`] is built for it along the way,
+    /// keeping failed attempts free of allocations.
+    pub(crate) fn attempt<R>(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> Result<R>,
+    ) -> Result<Option<R>> {
+        let checkpoint = self.checkpoint();
+        self.attempts += 1;
+        let result = f(self);
+        self.attempts -= 1;
+        match result {
+            Ok(value) => {
+                self.commit(checkpoint);
+                Ok(Some(value))
+            }
+            Err(_) => {
+                self.restore(checkpoint)?;
+                Ok(None)
+            }
+        }
+    }
+
+    /// Returns the number of bytes consumed so far.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Returns the in-memory input that has not been consumed yet.
+    ///
+    /// Parsers reading from a source that doesn't hold its input in memory (e.g. a
+    /// reader) return an empty slice.
+    pub fn input(&self) -> &'de [u8] {
+        self.source.input()
+    }
+
+    /// Returns the length of [`input`](Self::input).
     pub fn size(&self) -> usize {
-        self.input.len()
+        self.input().len()
+    }
+
+    /// Marks the current position, so the parser can go back to it with
+    /// [`restore`](Self::restore).
+    ///
+    /// Every checkpoint must be given back to either `restore` or [`commit`](Self::commit),
+    /// the latest first. Until then, reader-backed parsers keep the bytes read in memory.
+    pub fn checkpoint(&mut self) -> Checkpoint {
+        self.source.retain();
+        Checkpoint {
+            position: self.position,
+        }
+    }
+
+    /// Goes back to `checkpoint`, so the bytes read since are read again.
+    pub fn restore(&mut self, checkpoint: Checkpoint) -> Result<()> {
+        let result = self.source.rewind(self.position - checkpoint.position);
+        self.source.release();
+        result?;
+
+        self.position = checkpoint.position;
+        Ok(())
+    }
+
+    /// Keeps the current position, giving up on going back to `checkpoint`.
+    pub fn commit(&mut self, checkpoint: Checkpoint) {
+        let _ = checkpoint;
+        self.source.release();
+    }
+
+    /// Returns the next byte without consuming it.
+    pub fn peek_u8(&mut self) -> Result<u8> {
+        let checkpoint = self.checkpoint();
+        let value = self.u8();
+        self.restore(checkpoint)?;
+        value
+    }
+
+    /// Returns a parser over the next `len` bytes only, sharing this parser's configuration.
+    ///
+    /// This is how a length-prefixed payload is parsed without reading past its end. Call
+    /// [`finish`](BinaryParser::finish) or [`finish_exact`](BinaryParser::finish_exact)
+    /// once done, to deal with the bytes left in the region.
+    pub fn take(&mut self, len: usize) -> BinaryParser<'_, TakeSource<'_, 'de>> {
+        BinaryParser {
+            marker: PhantomData,
+            config: self.config,
+            position: self.position,
+            depth: self.depth,
+            attempts: self.attempts,
+            source: TakeSource {
+                parent: self,
+                remaining: len,
+            },
+        }
+    }
+
+    /// Consumes the next `len` bytes without parsing them.
+    pub fn skip(&mut self, len: usize) -> Result<()> {
+        self.read_bytes(len)?;
+        Ok(())
+    }
+
+    /// Consumes the next bytes, checking they are `expected`.
+    ///
+    /// Fails with [`Error::MagicMismatch`] if they are not, as with a wrong magic number,
+    /// leaving the parser where those bytes start.
+    pub fn expect_bytes(&mut self, expected: &[u8]) -> Result<()> {
+        let checkpoint = self.checkpoint();
+        let found = match self.read_bytes(expected.len()) {
+            Ok(found) => found,
+            Err(error) => {
+                self.restore(checkpoint)?;
+                return Err(error);
+            }
+        };
+        if *found != *expected {
+            let found = found.into_owned();
+            self.restore(checkpoint)?;
+            return Err(Error::MagicMismatch {
+                expected: expected.to_vec(),
+                found,
+            });
+        }
+        self.commit(checkpoint);
+        Ok(())
+    }
+
+    /// Consumes `len` padding bytes.
+    ///
+    /// With a `fill` byte, fails with [`Error::InvalidPadding`] if any of them is different.
+    pub fn skip_padding(&mut self, len: usize, fill: Option<u8>) -> Result<()> {
+        let found = self.read_bytes(len)?;
+        if let Some(fill) = fill
+            && found.iter().any(|&byte| byte != fill)
+        {
+            return Err(Error::InvalidPadding {
+                expected: fill,
+                found: found.into_owned(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Consumes padding bytes up to the next position that is a multiple of `align`,
+    /// as [`skip_padding`](Self::skip_padding) does.
+    pub fn skip_to_alignment(&mut self, align: usize, fill: Option<u8>) -> Result<()> {
+        let len = self.position.next_multiple_of(align) - self.position;
+        self.skip_padding(len, fill)
+    }
+
+    /// Checks the input left after a complete parse against the configured
+    /// [`TrailingBytesStrategy`], failing with [`Error::TrailingBytes`] if it must be rejected.
+    pub fn check_trailing_bytes(&self) -> Result<()> {
+        if self.config.trailing_bytes_strategy == TrailingBytesStrategy::Reject
+            && let Some(count) = self.source.remaining()
+            && count > 0
+        {
+            return Err(Error::TrailingBytes {
+                offset: self.position,
+                count,
+            });
+        }
+        Ok(())
     }
 
     /// Checks if the parser buffer is empty.
+    ///
+    /// Sources that can't tell how much input is left (e.g. readers) are never reported empty.
     pub fn is_empty(&self) -> bool {
-        self.input.is_empty()
+        self.source.remaining() == Some(0)
+    }
+
+    /// Returns how many elements a container announcing `len` of them may preallocate.
+    ///
+    /// Lengths come from the input and can't be trusted, so the capacity is bounded by the
+    /// bytes left in the input and under the limit, and by a fixed memory budget. Containers
+    /// still grow past this as their elements are actually parsed.
+    pub fn capacity_hint<T>(&self, len: usize) -> usize {
+        let mut available = self.source.remaining().unwrap_or(usize::MAX);
+        if let Some(limit) = self.config.limit {
+            available = available.min(limit.saturating_sub(self.position));
+        }
+
+        len.min(available)
+            .min(MAX_PREALLOCATION / core::mem::size_of::<T>().max(1))
+    }
+
+    /// Checks that `len` more bytes can be consumed without going past the configured limit.
+    fn check_limit(&self, len: usize) -> Result<()> {
+        let size = self.position.saturating_add(len);
+        if let Some(limit) = self.config.limit
+            && size > limit
+        {
+            return Err(Error::LimitExceeded { limit, size });
+        }
+        Ok(())
+    }
+
+    /// Fills `buf` from the source, refusing to go past the configured limit.
+    fn read(&mut self, buf: &mut [u8]) -> Result<()> {
+        self.check_limit(buf.len())?;
+        self.source.read(buf)?;
+        self.position += buf.len();
+        Ok(())
+    }
+
+    /// Consumes `len` bytes from the source, refusing to go past the configured limit.
+    fn read_bytes(&mut self, len: usize) -> Result<Cow<'de, [u8]>> {
+        self.check_limit(len)?;
+        let value = self.source.read_bytes(len)?;
+        self.position += len;
+        Ok(value)
+    }
+
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        let mut buf = [0; N];
+        self.read(&mut buf)?;
+        Ok(buf)
     }
 
     pub fn bool(&mut self) -> Result<bool> {
-        match self.input.try_get_u8()? {
+        match self.u8()? {
             0 => Ok(false),
             1 => Ok(true),
             x => Err(Error::InvalidBoolValue(x)),
@@ -40,166 +489,276 @@ impl<'de> BinaryParser<'de> {
     }
 
     pub fn i8(&mut self) -> Result<i8> {
-        Ok(self.input.try_get_i8()?)
+        Ok(i8::from_le_bytes(self.read_array()?))
     }
 
     pub fn i16(&mut self) -> Result<i16> {
-        let value = match self.config.endianness_strategy {
-            EndiannessStrategy::Little => self.input.try_get_i16_le()?,
-            EndiannessStrategy::Big => self.input.try_get_i16()?,
+        let value = match (
+            self.config.integer_encoding_strategy,
+            self.config.endianness_strategy,
+        ) {
+            (IntegerEncodingStrategy::Varint, _) => {
+                let v = self.read_varint(u16::BITS)? as u16;
+                ((v >> 1) as i16) ^ -((v & 1) as i16)
+            }
+            (_, EndiannessStrategy::Little) => i16::from_le_bytes(self.read_array()?),
+            (_, EndiannessStrategy::Big) => i16::from_be_bytes(self.read_array()?),
         };
         Ok(value)
     }
 
     pub fn i32(&mut self) -> Result<i32> {
-        let value = match self.config.endianness_strategy {
-            EndiannessStrategy::Little => self.input.try_get_i32_le()?,
-            EndiannessStrategy::Big => self.input.try_get_i32()?,
+        let value = match (
+            self.config.integer_encoding_strategy,
+            self.config.endianness_strategy,
+        ) {
+            (IntegerEncodingStrategy::Varint, _) => {
+                let v = self.read_varint(u32::BITS)? as u32;
+                ((v >> 1) as i32) ^ -((v & 1) as i32)
+            }
+            (_, EndiannessStrategy::Little) => i32::from_le_bytes(self.read_array()?),
+            (_, EndiannessStrategy::Big) => i32::from_be_bytes(self.read_array()?),
         };
         Ok(value)
     }
 
     pub fn i64(&mut self) -> Result<i64> {
-        let value = match self.config.endianness_strategy {
-            EndiannessStrategy::Little => self.input.try_get_i64_le()?,
-            EndiannessStrategy::Big => self.input.try_get_i64()?,
+        let value = match (
+            self.config.integer_encoding_strategy,
+            self.config.endianness_strategy,
+        ) {
+            (IntegerEncodingStrategy::Varint, _) => {
+                let v = self.read_varint(u64::BITS)? as u64;
+                ((v >> 1) as i64) ^ -((v & 1) as i64)
+            }
+            (_, EndiannessStrategy::Little) => i64::from_le_bytes(self.read_array()?),
+            (_, EndiannessStrategy::Big) => i64::from_be_bytes(self.read_array()?),
         };
         Ok(value)
     }
 
     pub fn i128(&mut self) -> Result<i128> {
-        let value = match self.config.endianness_strategy {
-            EndiannessStrategy::Little => self.input.try_get_i128_le()?,
-            EndiannessStrategy::Big => self.input.try_get_i128()?,
+        let value = match (
+            self.config.integer_encoding_strategy,
+            self.config.endianness_strategy,
+        ) {
+            (IntegerEncodingStrategy::Varint, _) => {
+                let v = self.read_varint(u128::BITS)?;
+                ((v >> 1) as i128) ^ -((v & 1) as i128)
+            }
+            (_, EndiannessStrategy::Little) => i128::from_le_bytes(self.read_array()?),
+            (_, EndiannessStrategy::Big) => i128::from_be_bytes(self.read_array()?),
         };
         Ok(value)
     }
 
     pub fn u8(&mut self) -> Result<u8> {
-        Ok(self.input.try_get_u8()?)
+        Ok(u8::from_le_bytes(self.read_array()?))
     }
 
     pub fn u16(&mut self) -> Result<u16> {
-        let value = match self.config.endianness_strategy {
-            EndiannessStrategy::Little => self.input.try_get_u16_le()?,
-            EndiannessStrategy::Big => self.input.try_get_u16()?,
+        let value = match (
+            self.config.integer_encoding_strategy,
+            self.config.endianness_strategy,
+        ) {
+            (IntegerEncodingStrategy::Varint, _) => self.read_varint(u16::BITS)? as u16,
+            (_, EndiannessStrategy::Little) => u16::from_le_bytes(self.read_array()?),
+            (_, EndiannessStrategy::Big) => u16::from_be_bytes(self.read_array()?),
         };
         Ok(value)
     }
 
     pub fn u32(&mut self) -> Result<u32> {
-        let value = match self.config.endianness_strategy {
-            EndiannessStrategy::Little => self.input.try_get_u32_le()?,
-            EndiannessStrategy::Big => self.input.try_get_u32()?,
+        let value = match (
+            self.config.integer_encoding_strategy,
+            self.config.endianness_strategy,
+        ) {
+            (IntegerEncodingStrategy::Varint, _) => self.read_varint(u32::BITS)? as u32,
+            (_, EndiannessStrategy::Little) => u32::from_le_bytes(self.read_array()?),
+            (_, EndiannessStrategy::Big) => u32::from_be_bytes(self.read_array()?),
         };
         Ok(value)
     }
 
     pub fn u64(&mut self) -> Result<u64> {
-        let value = match self.config.endianness_strategy {
-            EndiannessStrategy::Little => self.input.try_get_u64_le()?,
-            EndiannessStrategy::Big => self.input.try_get_u64()?,
+        let value = match (
+            self.config.integer_encoding_strategy,
+            self.config.endianness_strategy,
+        ) {
+            (IntegerEncodingStrategy::Varint, _) => self.read_varint(u64::BITS)? as u64,
+            (_, EndiannessStrategy::Little) => u64::from_le_bytes(self.read_array()?),
+            (_, EndiannessStrategy::Big) => u64::from_be_bytes(self.read_array()?),
         };
         Ok(value)
     }
 
     pub fn u128(&mut self) -> Result<u128> {
-        let value = match self.config.endianness_strategy {
-            EndiannessStrategy::Little => self.input.try_get_u128_le()?,
-            EndiannessStrategy::Big => self.input.try_get_u128()?,
+        let value = match (
+            self.config.integer_encoding_strategy,
+            self.config.endianness_strategy,
+        ) {
+            (IntegerEncodingStrategy::Varint, _) => self.read_varint(u128::BITS)?,
+            (_, EndiannessStrategy::Little) => u128::from_le_bytes(self.read_array()?),
+            (_, EndiannessStrategy::Big) => u128::from_be_bytes(self.read_array()?),
         };
         Ok(value)
     }
 
     pub fn f32(&mut self) -> Result<f32> {
         let value = match self.config.endianness_strategy {
-            EndiannessStrategy::Little => self.input.try_get_f32_le()?,
-            EndiannessStrategy::Big => self.input.try_get_f32()?,
+            EndiannessStrategy::Little => f32::from_le_bytes(self.read_array()?),
+            EndiannessStrategy::Big => f32::from_be_bytes(self.read_array()?),
         };
         Ok(value)
     }
 
     pub fn f64(&mut self) -> Result<f64> {
         let value = match self.config.endianness_strategy {
-            EndiannessStrategy::Little => self.input.try_get_f64_le()?,
-            EndiannessStrategy::Big => self.input.try_get_f64()?,
+            EndiannessStrategy::Little => f64::from_le_bytes(self.read_array()?),
+            EndiannessStrategy::Big => f64::from_be_bytes(self.read_array()?),
         };
         Ok(value)
     }
 
     pub fn char(&mut self) -> Result<char> {
-        if self.input.is_empty() {
-            return Err(Error::NoEnoughData {
-                expected: 1,
-                available: 0,
-            });
-        }
-
-        let len = if self.input.len() < 4 {
-            self.input.len()
-        } else {
-            4
+        let mut buf = [0u8; 4];
+        self.read(&mut buf[..1])?;
+
+        // the leading byte tells how long the UTF-8 sequence is
+        let len = match buf[0] {
+            0x00..=0x7f => 1,
+            0xc0..=0xdf => 2,
+            0xe0..=0xef => 3,
+            0xf0..=0xf7 => 4,
+            _ => {
+                return Err(Error::InvalidUtf8 {
+                    value: buf[..1].to_vec(),
+                });
+            }
         };
+        self.read(&mut buf[1..len])?;
 
-        let result = std::str::from_utf8(&self.input[..len])
-            .map_err(|_| Error::InvalidUtf8 {
-                value: self.input.to_vec(),
-            })?
-            .chars()
-            .next()
+        core::str::from_utf8(&buf[..len])
+            .ok()
+            .and_then(|s| s.chars().next())
             .ok_or(Error::InvalidUtf8 {
-                value: self.input.to_vec(),
-            })?;
-
-        let char_len = result.len_utf8();
-
-        self.input = &self.input[char_len..];
-        Ok(result)
+                value: buf[..len].to_vec(),
+            })
     }
 
-    pub fn string(&mut self) -> Result<&'de str> {
+    pub fn string(&mut self) -> Result<Cow<'de, str>> {
         let len = self.container_size()?;
 
-        if self.input.len() < len {
-            return Err(Error::NoEnoughData {
-                expected: len,
-                available: self.input.len(),
-            });
+        match self.read_bytes(len)? {
+            Cow::Borrowed(value) => {
+                core::str::from_utf8(value)
+                    .map(Cow::Borrowed)
+                    .map_err(|_| Error::InvalidUtf8 {
+                        value: value.to_vec(),
+                    })
+            }
+            Cow::Owned(value) => {
+                String::from_utf8(value)
+                    .map(Cow::Owned)
+                    .map_err(|e| Error::InvalidUtf8 {
+                        value: e.into_bytes(),
+                    })
+            }
         }
+    }
 
-        let value = std::str::from_utf8(&self.input[..len])
-            .ok()
-            .ok_or(Error::InvalidUtf8 {
-                value: self.input[..len].to_vec(),
-            })?;
+    pub fn bytes(&mut self, size: usize) -> Result<Cow<'de, [u8]>> {
+        self.read_bytes(size)
+    }
 
-        self.input = &self.input[len..];
-        Ok(value)
+    pub fn container_size(&mut self) -> Result<usize> {
+        // fixed-width lengths keep their width whatever the integer encoding is
+        let config = self.config.with_fixed_int_encoding();
+        let size = self.scoped_config(config, |parser| {
+            let size = match parser.config.container_length_strategy {
+                ContainerLengthStrategy::OneByte => parser.u8()? as usize,
+                ContainerLengthStrategy::TwoBytes => parser.u16()? as usize,
+                ContainerLengthStrategy::FourBytes => parser.u32()? as usize,
+                ContainerLengthStrategy::EightBytes => to_length(parser.u64()? as u128)?,
+                ContainerLengthStrategy::SixteenBytes => to_length(parser.u128()?)?,
+                ContainerLengthStrategy::Varint => parser.read_varint(usize::BITS)? as usize,
+            };
+            Ok(size)
+        })?;
+
+        Ok(size)
     }
 
-    pub fn bytes(&mut self, size: usize) -> Result<&'de [u8]> {
-        if self.input.len() < size {
-            return Err(Error::NoEnoughData {
-                expected: size,
-                available: self.input.len(),
-            });
+    /// Reads an unsigned LEB128 varint holding at most `bits` bits.
+    ///
+    /// Encodings with needless trailing zero groups, or with a value that doesn't fit in
+    /// `bits` bits, are rejected with [`Error::InvalidVarint`].
+    fn read_varint(&mut self, bits: u32) -> Result<u128> {
+        let max_len = bits.div_ceil(7) as usize;
+        let mut buf = [0u8; 19];
+        let mut value = 0u128;
+
+        for (i, slot) in buf.iter_mut().enumerate().take(max_len) {
+            let byte = self.u8()?;
+            *slot = byte;
+
+            let shift = 7 * i as u32;
+            let group = (byte & 0x7f) as u128;
+            // the last group may only use the bits left in the target type
+            if shift + 7 > bits && group >> (bits - shift) != 0 {
+                return Err(Error::InvalidVarint {
+                    bytes: buf[..=i].to_vec(),
+                });
+            }
+            value |= group << shift;
+
+            if byte & 0x80 == 0 {
+                // a zero final group only pads the encoding
+                if i > 0 && byte == 0 {
+                    return Err(Error::InvalidVarint {
+                        bytes: buf[..=i].to_vec(),
+                    });
+                }
+                return Ok(value);
+            }
         }
 
-        let value = &self.input[..size];
-
-        self.input = &self.input[size..];
-        Ok(value)
+        Err(Error::InvalidVarint {
+            bytes: buf[..max_len].to_vec(),
+        })
     }
+}
 
-    pub fn container_size(&mut self) -> Result<usize> {
-        let size = match self.config.container_length_strategy {
-            ContainerLengthStrategy::OneByte => self.u8()? as usize,
-            ContainerLengthStrategy::TwoBytes => self.u16()? as usize,
-            ContainerLengthStrategy::FourBytes => self.u32()? as usize,
-            ContainerLengthStrategy::EightBytes => self.u64()? as usize,
-            ContainerLengthStrategy::SixteenBytes => self.u128()? as usize,
-        };
+impl<'a, 'de> BinaryParser<'a, TakeSource<'a, 'de>> {
+    /// Ends a region created by [`take`](BinaryParser::take), skipping the bytes left in
+    /// it.
+    ///
+    /// Regions may hold data a parser doesn't know about, so leftover bytes are fine here
+    /// whatever the [`TrailingBytesStrategy`], which only applies to the whole input. Use
+    /// [`finish_exact`](BinaryParser::finish_exact) to reject them instead.
+    pub fn finish(&mut self) -> Result<()> {
+        let count = self.source.remaining;
+        self.skip(count)
+    }
 
-        Ok(size)
+    /// Ends a region created by [`take`](BinaryParser::take), failing with
+    /// [`Error::TrailingBytes`] if it wasn't read entirely.
+    pub fn finish_exact(&mut self) -> Result<()> {
+        let count = self.source.remaining;
+        if count > 0 {
+            return Err(Error::TrailingBytes {
+                offset: self.position,
+                count,
+            });
+        }
+        Ok(())
     }
 }
+
+/// Converts a length prefix to a `usize`, failing if it doesn't fit on this platform.
+pub(crate) fn to_length(value: u128) -> Result<usize> {
+    usize::try_from(value).map_err(|_| Error::Overflow {
+        value: value.to_string(),
+        max: usize::MAX.to_string(),
+    })
+}
@@ -1,3 +1,5 @@
+use alloc::borrow::Cow;
+
 use serde::de::value::U8Deserializer;
 use serde::de::{self, DeserializeSeed, IntoDeserializer, Visitor};
 use serde::{Deserialize, Deserializer};
@@ -5,9 +7,9 @@ use serde::{Deserialize, Deserializer};
 use crate::config::OptionalStrategy;
 use crate::error::{Error, Result};
 
-use super::BinaryParser;
+use super::{BinaryParser, source::SliceSource};
 
-impl<'de> de::Deserializer<'de> for &mut BinaryParser<'de> {
+impl<'de> de::Deserializer<'de> for &mut BinaryParser<'de, SliceSource<'de>> {
     type Error = Error;
 
     fn is_human_readable(&self) -> bool {
@@ -56,7 +58,7 @@ impl<'de> de::Deserializer<'de> for &mut BinaryParser<'de> {
         visitor.visit_i64(self.i64()?)
     }
 
-    fn deserialize_i128<V>(self, visitor: V) -> std::result::Result<V::Value, Self::Error>
+    fn deserialize_i128<V>(self, visitor: V) -> core::result::Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
@@ -91,7 +93,7 @@ impl<'de> de::Deserializer<'de> for &mut BinaryParser<'de> {
         visitor.visit_u64(self.u64()?)
     }
 
-    fn deserialize_u128<V>(self, visitor: V) -> std::result::Result<V::Value, Self::Error>
+    fn deserialize_u128<V>(self, visitor: V) -> core::result::Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
@@ -123,7 +125,10 @@ impl<'de> de::Deserializer<'de> for &mut BinaryParser<'de> {
     where
         V: Visitor<'de>,
     {
-        visitor.visit_borrowed_str(self.string()?)
+        match self.string()? {
+            Cow::Borrowed(value) => visitor.visit_borrowed_str(value),
+            Cow::Owned(value) => visitor.visit_string(value),
+        }
     }
 
     fn deserialize_string<V>(self, visitor: V) -> Result<V::Value>
@@ -182,22 +187,24 @@ impl<'de> de::Deserializer<'de> for &mut BinaryParser<'de> {
     where
         V: Visitor<'de>,
     {
-        visitor.visit_newtype_struct(self)
+        self.nested(|de| visitor.visit_newtype_struct(de))
     }
 
     fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        let len = self.container_size()?;
-        visitor.visit_seq(SeqAccess::new(self, len))
+        self.nested(|de| {
+            let len = de.container_size()?;
+            visitor.visit_seq(SeqAccess::new(de, len))
+        })
     }
 
     fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_seq(SeqAccess::new(self, len))
+        self.nested(|de| visitor.visit_seq(SeqAccess::new(de, len)))
     }
 
     fn deserialize_tuple_struct<V>(
@@ -216,8 +223,10 @@ impl<'de> de::Deserializer<'de> for &mut BinaryParser<'de> {
     where
         V: Visitor<'de>,
     {
-        let len = self.container_size()?;
-        visitor.visit_map(MapAccess::new(self, len))
+        self.nested(|de| {
+            let len = de.container_size()?;
+            visitor.visit_map(MapAccess::new(de, len))
+        })
     }
 
     fn deserialize_struct<V>(
@@ -229,7 +238,7 @@ impl<'de> de::Deserializer<'de> for &mut BinaryParser<'de> {
     where
         V: Visitor<'de>,
     {
-        visitor.visit_map(StructAccess::new(self, fields))
+        self.nested(|de| visitor.visit_map(StructAccess::new(de, fields)))
     }
 
     fn deserialize_enum<V>(
@@ -241,7 +250,7 @@ impl<'de> de::Deserializer<'de> for &mut BinaryParser<'de> {
     where
         V: Visitor<'de>,
     {
-        visitor.visit_enum(EnumAccess::new(self))
+        self.nested(|de| visitor.visit_enum(EnumAccess::new(de)))
     }
 
     fn deserialize_identifier<V>(self, _visitor: V) -> Result<V::Value>
@@ -260,13 +269,13 @@ impl<'de> de::Deserializer<'de> for &mut BinaryParser<'de> {
 }
 
 struct SeqAccess<'a, 'de: 'a> {
-    de: &'a mut BinaryParser<'de>,
+    de: &'a mut BinaryParser<'de, SliceSource<'de>>,
     len: usize,
     index: usize,
 }
 
 impl<'de, 'a> SeqAccess<'a, 'de> {
-    fn new(de: &'a mut BinaryParser<'de>, len: usize) -> Self {
+    fn new(de: &'a mut BinaryParser<'de, SliceSource<'de>>, len: usize) -> Self {
         SeqAccess { de, len, index: 0 }
     }
 }
@@ -287,18 +296,21 @@ impl<'de> serde::de::SeqAccess<'de> for SeqAccess<'_, 'de> {
     }
 
     fn size_hint(&self) -> Option<usize> {
-        Some(self.len - self.index)
+        Some(self.de.capacity_hint::<u8>(self.len - self.index))
     }
 }
 
 struct StructAccess<'a, 'de: 'a> {
-    de: &'a mut BinaryParser<'de>,
+    de: &'a mut BinaryParser<'de, SliceSource<'de>>,
     fields: &'static [&'static str],
     index: usize,
 }
 
 impl<'a, 'de> StructAccess<'a, 'de> {
-    fn new(de: &'a mut BinaryParser<'de>, fields: &'static [&'static str]) -> Self {
+    fn new(
+        de: &'a mut BinaryParser<'de, SliceSource<'de>>,
+        fields: &'static [&'static str],
+    ) -> Self {
         StructAccess {
             de,
             fields,
@@ -332,13 +344,13 @@ impl<'de> serde::de::MapAccess<'de> for StructAccess<'_, 'de> {
 }
 
 struct MapAccess<'a, 'de: 'a> {
-    de: &'a mut BinaryParser<'de>,
+    de: &'a mut BinaryParser<'de, SliceSource<'de>>,
     len: usize,
     index: usize,
 }
 
 impl<'de, 'a> MapAccess<'a, 'de> {
-    fn new(de: &'a mut BinaryParser<'de>, len: usize) -> Self {
+    fn new(de: &'a mut BinaryParser<'de, SliceSource<'de>>, len: usize) -> Self {
         MapAccess { de, len, index: 0 }
     }
 }
@@ -364,14 +376,18 @@ impl<'de> serde::de::MapAccess<'de> for MapAccess<'_, 'de> {
     {
         seed.deserialize(&mut *self.de)
     }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.de.capacity_hint::<u8>(self.len - self.index))
+    }
 }
 
 struct EnumAccess<'a, 'de: 'a> {
-    de: &'a mut BinaryParser<'de>,
+    de: &'a mut BinaryParser<'de, SliceSource<'de>>,
 }
 
 impl<'de, 'a> EnumAccess<'a, 'de> {
-    fn new(de: &'a mut BinaryParser<'de>) -> Self {
+    fn new(de: &'a mut BinaryParser<'de, SliceSource<'de>>) -> Self {
         EnumAccess { de }
     }
 }
@@ -389,11 +405,11 @@ impl<'de> serde::de::EnumAccess<'de> for EnumAccess<'_, 'de> {
         Ok((val, self))
     }
 
-    fn variant<V>(self) -> std::result::Result<(V, Self::Variant), Self::Error>
+    fn variant<V>(self) -> core::result::Result<(V, Self::Variant), Self::Error>
     where
         V: Deserialize<'de>,
     {
-        self.variant_seed(std::marker::PhantomData)
+        self.variant_seed(core::marker::PhantomData)
     }
 }
 
@@ -427,11 +443,11 @@ impl<'de> serde::de::VariantAccess<'de> for EnumAccess<'_, 'de> {
 }
 
 struct SizelessAccess<'a, 'de: 'a> {
-    de: &'a mut BinaryParser<'de>,
+    de: &'a mut BinaryParser<'de, SliceSource<'de>>,
 }
 
 impl<'de, 'a> SizelessAccess<'a, 'de> {
-    fn new(de: &'a mut BinaryParser<'de>) -> Self {
+    fn new(de: &'a mut BinaryParser<'de, SliceSource<'de>>) -> Self {
         SizelessAccess { de }
     }
 }
@@ -0,0 +1,320 @@
+use alloc::{borrow::Cow, string::String};
+#[cfg(feature = "std")]
+use std::io::Read;
+
+use crate::error::{Error, Result};
+
+/// The input a [`BinaryParser`](crate::BinaryParser) pulls its bytes from.
+///
+/// `'de` is the lifetime of data that can be borrowed straight from the input,
+/// which lets string and byte parsing avoid copies when the whole input is in memory.
+pub trait Source<'de> {
+    /// Fills `buf` with the next bytes of input.
+    ///
+    /// Fails with [`Error::NoEnoughData`] if the input ends before `buf` is full.
+    fn read(&mut self, buf: &mut [u8]) -> Result<()>;
+
+    /// Consumes the next `len` bytes, borrowing them from the input when possible.
+    fn read_bytes(&mut self, len: usize) -> Result<Cow<'de, [u8]>>;
+
+    /// Returns the bytes left in the input, if the source holds them in memory.
+    ///
+    /// Sources that don't (e.g. readers) return an empty slice.
+    fn input(&self) -> &'de [u8] {
+        &[]
+    }
+
+    /// Returns the number of bytes left in the input, if it is known.
+    fn remaining(&self) -> Option<usize>;
+
+    /// Starts keeping the bytes consumed from now on, so they can be rewound over.
+    ///
+    /// Calls nest: every `retain` is matched by a [`release`](Source::release), and bytes
+    /// are kept while at least one is active. In-memory sources keep everything anyway.
+    fn retain(&mut self) {}
+
+    /// Stops keeping the bytes consumed since the matching [`retain`](Source::retain).
+    fn release(&mut self) {}
+
+    /// Moves back `len` bytes, so they are read again.
+    ///
+    /// Only bytes consumed since the oldest active [`retain`](Source::retain) can be
+    /// rewound over. Sources that can't go back fail with [`Error::Message`].
+    fn rewind(&mut self, len: usize) -> Result<()> {
+        let _ = len;
+        Err(Error::Message(String::from(
+            "this source doesn't support rewinding",
+        )))
+    }
+}
+
+impl<'de, S> Source<'de> for &mut S
+where
+    S: Source<'de> + ?Sized,
+{
+    fn read(&mut self, buf: &mut [u8]) -> Result<()> {
+        (**self).read(buf)
+    }
+
+    fn read_bytes(&mut self, len: usize) -> Result<Cow<'de, [u8]>> {
+        (**self).read_bytes(len)
+    }
+
+    fn input(&self) -> &'de [u8] {
+        (**self).input()
+    }
+
+    fn remaining(&self) -> Option<usize> {
+        (**self).remaining()
+    }
+
+    fn retain(&mut self) {
+        (**self).retain()
+    }
+
+    fn release(&mut self) {
+        (**self).release()
+    }
+
+    fn rewind(&mut self, len: usize) -> Result<()> {
+        (**self).rewind(len)
+    }
+}
+
+/// A [`Source`] over an in-memory byte slice.
+#[derive(Debug, Clone, Copy)]
+pub struct SliceSource<'de> {
+    data: &'de [u8],
+    offset: usize,
+}
+
+impl<'de> SliceSource<'de> {
+    /// Creates a new `SliceSource` reading from `input`.
+    pub fn new(input: &'de [u8]) -> Self {
+        Self {
+            data: input,
+            offset: 0,
+        }
+    }
+
+    /// Returns the bytes that have not been consumed yet.
+    #[inline]
+    pub fn input(&self) -> &'de [u8] {
+        &self.data[self.offset..]
+    }
+
+    #[inline]
+    fn take(&mut self, len: usize) -> Result<&'de [u8]> {
+        let input = self.input();
+        if input.len() < len {
+            return Err(Error::NoEnoughData {
+                expected: len,
+                available: input.len(),
+            });
+        }
+
+        self.offset += len;
+        Ok(&input[..len])
+    }
+}
+
+impl<'de> Source<'de> for SliceSource<'de> {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> Result<()> {
+        buf.copy_from_slice(self.take(buf.len())?);
+        Ok(())
+    }
+
+    #[inline]
+    fn read_bytes(&mut self, len: usize) -> Result<Cow<'de, [u8]>> {
+        Ok(Cow::Borrowed(self.take(len)?))
+    }
+
+    #[inline]
+    fn input(&self) -> &'de [u8] {
+        SliceSource::input(self)
+    }
+
+    #[inline]
+    fn remaining(&self) -> Option<usize> {
+        Some(self.data.len() - self.offset)
+    }
+
+    fn rewind(&mut self, len: usize) -> Result<()> {
+        self.offset = self.offset.checked_sub(len).ok_or_else(|| {
+            Error::Message(String::from("can't rewind before the start of the input"))
+        })?;
+        Ok(())
+    }
+}
+
+/// A [`Source`] pulling bytes from any [`std::io::Read`] (a file, a socket, ...).
+///
+/// Bytes are read only when a parse requests them, so the reader is left positioned
+/// right after the parsed value. Every request goes to the reader, so wrap unbuffered
+/// readers in a [`std::io::BufReader`].
+///
+/// While a parser checkpoint is active, the bytes read are also kept in memory so the
+/// parser can go back to it.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct IoSource<R> {
+    reader: R,
+    // bytes read since the oldest active `retain`, the ones from `replay` on
+    // were rewound over and are served again before reading more
+    retained: Vec<u8>,
+    replay: usize,
+    // number of active `retain` calls
+    retaining: usize,
+}
+
+#[cfg(feature = "std")]
+impl<R> IoSource<R>
+where
+    R: std::io::Read,
+{
+    /// Creates a new `IoSource` reading from `reader`.
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            retained: Vec::new(),
+            replay: 0,
+            retaining: 0,
+        }
+    }
+
+    /// Returns a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    /// Consumes the source and returns the underlying reader.
+    ///
+    /// Bytes that were rewound over but not read again are lost.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Serves as many rewound bytes as possible into `buf`, returning how many.
+    fn read_replay(&mut self, buf: &mut [u8]) -> usize {
+        let pending = &self.retained[self.replay..];
+        let len = pending.len().min(buf.len());
+        buf[..len].copy_from_slice(&pending[..len]);
+        self.replay += len;
+        self.trim();
+        len
+    }
+
+    /// Drops the kept bytes once nothing needs them anymore.
+    fn trim(&mut self) {
+        if self.retaining == 0 && self.replay == self.retained.len() {
+            self.retained.clear();
+            self.replay = 0;
+        }
+    }
+
+    /// Keeps freshly read bytes if a checkpoint may come back over them.
+    fn keep(&mut self, bytes: &[u8]) {
+        // fresh bytes are only read once the rewound ones are all served again
+        if self.retaining > 0 && !bytes.is_empty() {
+            self.retained.extend_from_slice(bytes);
+            self.replay = self.retained.len();
+        }
+    }
+
+    /// Gives back the last `len` bytes of a failed read, so that it doesn't consume
+    /// anything a checkpoint may need.
+    fn unread(&mut self, len: usize) {
+        if self.retaining > 0 {
+            self.replay -= len;
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'de, R> Source<'de> for IoSource<R>
+where
+    R: std::io::Read,
+{
+    fn read(&mut self, buf: &mut [u8]) -> Result<()> {
+        let mut filled = self.read_replay(buf);
+        let start = filled;
+        let result = loop {
+            if filled == buf.len() {
+                break Ok(());
+            }
+            match self.reader.read(&mut buf[filled..]) {
+                Ok(0) => {
+                    break Err(Error::NoEnoughData {
+                        expected: buf.len(),
+                        available: filled,
+                    });
+                }
+                Ok(n) => filled += n,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(e) => break Err(e.into()),
+            }
+        };
+
+        self.keep(&buf[start..filled]);
+        if result.is_err() {
+            self.unread(filled);
+        }
+        result
+    }
+
+    fn read_bytes(&mut self, len: usize) -> Result<Cow<'de, [u8]>> {
+        let pending = (self.retained.len() - self.replay).min(len);
+        let mut value = self.retained[self.replay..self.replay + pending].to_vec();
+        self.replay += pending;
+        self.trim();
+
+        // grow the buffer as bytes arrive instead of trusting `len` up front
+        let result = self
+            .reader
+            .by_ref()
+            .take((len - pending) as u64)
+            .read_to_end(&mut value);
+        self.keep(&value[pending..]);
+
+        if let Err(e) = result {
+            self.unread(value.len());
+            return Err(e.into());
+        }
+        if value.len() < len {
+            self.unread(value.len());
+            return Err(Error::NoEnoughData {
+                expected: len,
+                available: value.len(),
+            });
+        }
+        Ok(Cow::Owned(value))
+    }
+
+    fn remaining(&self) -> Option<usize> {
+        None
+    }
+
+    fn retain(&mut self) {
+        if self.retaining == 0 {
+            // only bytes from the new checkpoint on can be needed again
+            self.retained.drain(..self.replay);
+            self.replay = 0;
+        }
+        self.retaining += 1;
+    }
+
+    fn release(&mut self) {
+        self.retaining -= 1;
+        self.trim();
+    }
+
+    fn rewind(&mut self, len: usize) -> Result<()> {
+        self.replay = self.replay.checked_sub(len).ok_or_else(|| {
+            Error::Message(String::from(
+                "can't rewind over bytes read before the checkpoint",
+            ))
+        })?;
+        Ok(())
+    }
+}
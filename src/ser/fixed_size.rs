@@ -0,0 +1,100 @@
+/// Types whose serialized form always has the same length, known at compile time.
+///
+/// `SIZE` is the number of bytes written by [`BinarySerialize`](crate::BinarySerialize)
+/// regardless of the value, which allows declaring buffers like `[u8; Header::SIZE]` and
+/// asserting packet layouts statically.
+///
+/// `#[derive(BinarySerialize)]` implements this trait for structs whose fields all have a
+/// fixed size (bit fields are packed as they are on the wire, skipped fields count as zero).
+///
+/// Sizes assume the default [`IntegerEncodingStrategy::Fixed`](crate::config::IntegerEncodingStrategy):
+/// with varint integers the actual output is usually shorter. Structs with a
+/// `#[binja(varint)]` field don't implement this trait.
+///
+/// # Example
+/// ```rust
+/// use binja::{BinarySerialize, FixedEncodedSize};
+///
+/// #[derive(BinarySerialize)]
+/// struct Header {
+///     #[binja(bits = 4)]
+///     version: u8,
+///     #[binja(bits = 4)]
+///     kind: u8,
+///     length: u16,
+///     checksum: [u8; 4],
+/// }
+///
+/// const _: () = assert!(Header::SIZE == 7);
+/// let buffer = [0u8; Header::SIZE];
+/// ```
+pub trait FixedEncodedSize {
+    /// The number of bytes every value of this type serializes to.
+    const SIZE: usize;
+}
+
+macro_rules! impl_fixed_encoded_size {
+    ($($ty:ty => $size:expr),+ $(,)?) => {
+        $(
+            impl FixedEncodedSize for $ty {
+                const SIZE: usize = $size;
+            }
+        )+
+    };
+}
+
+impl_fixed_encoded_size!(
+    () => 0,
+    bool => 1,
+    i8 => 1,
+    i16 => 2,
+    i32 => 4,
+    i64 => 8,
+    i128 => 16,
+    u8 => 1,
+    u16 => 2,
+    u32 => 4,
+    u64 => 8,
+    u128 => 16,
+    f32 => 4,
+    f64 => 8,
+);
+
+impl<T, const N: usize> FixedEncodedSize for [T; N]
+where
+    T: FixedEncodedSize,
+{
+    const SIZE: usize = T::SIZE * N;
+}
+
+macro_rules! impl_fixed_encoded_size_for_tuple {
+    ($($name:ident),+) => {
+        impl<$($name),+> FixedEncodedSize for ($($name,)+)
+        where
+            $($name: FixedEncodedSize,)+
+        {
+            const SIZE: usize = 0 $(+ $name::SIZE)+;
+        }
+    };
+}
+
+impl_fixed_encoded_size_for_tuple!(T1);
+impl_fixed_encoded_size_for_tuple!(T1, T2);
+impl_fixed_encoded_size_for_tuple!(T1, T2, T3);
+impl_fixed_encoded_size_for_tuple!(T1, T2, T3, T4);
+impl_fixed_encoded_size_for_tuple!(T1, T2, T3, T4, T5);
+impl_fixed_encoded_size_for_tuple!(T1, T2, T3, T4, T5, T6);
+impl_fixed_encoded_size_for_tuple!(T1, T2, T3, T4, T5, T6, T7);
+impl_fixed_encoded_size_for_tuple!(T1, T2, T3, T4, T5, T6, T7, T8);
+impl_fixed_encoded_size_for_tuple!(T1, T2, T3, T4, T5, T6, T7, T8, T9);
+impl_fixed_encoded_size_for_tuple!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10);
+impl_fixed_encoded_size_for_tuple!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11);
+impl_fixed_encoded_size_for_tuple!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12);
+impl_fixed_encoded_size_for_tuple!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13);
+impl_fixed_encoded_size_for_tuple!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14);
+impl_fixed_encoded_size_for_tuple!(
+    T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15
+);
+impl_fixed_encoded_size_for_tuple!(
+    T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15, T16
+);
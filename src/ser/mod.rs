@@ -1,107 +1,112 @@
+use alloc::{string::String, vec::Vec};
+
 use serializer::BinarySerializer;
+use sink::Sink;
 
 use crate::error::Result;
 
 #[cfg(feature = "serde")]
 mod serde_impl;
 
+pub mod fixed_size;
 pub mod serializer;
+pub mod sink;
 
 pub trait BinarySerialize {
-    fn binary_serialize(&self, serializer: &mut BinarySerializer) -> Result<()>;
+    fn binary_serialize<S: Sink>(&self, serializer: &mut BinarySerializer<S>) -> Result<()>;
 }
 
 impl BinarySerialize for () {
-    fn binary_serialize(&self, _serializer: &mut BinarySerializer) -> Result<()> {
+    fn binary_serialize<S: Sink>(&self, _serializer: &mut BinarySerializer<S>) -> Result<()> {
         Ok(())
     }
 }
 
 impl BinarySerialize for bool {
-    fn binary_serialize(&self, serializer: &mut BinarySerializer) -> Result<()> {
+    fn binary_serialize<S: Sink>(&self, serializer: &mut BinarySerializer<S>) -> Result<()> {
         serializer.bool(*self)
     }
 }
 
 impl BinarySerialize for i8 {
-    fn binary_serialize(&self, serializer: &mut BinarySerializer) -> Result<()> {
+    fn binary_serialize<S: Sink>(&self, serializer: &mut BinarySerializer<S>) -> Result<()> {
         serializer.i8(*self)
     }
 }
 impl BinarySerialize for i16 {
-    fn binary_serialize(&self, serializer: &mut BinarySerializer) -> Result<()> {
+    fn binary_serialize<S: Sink>(&self, serializer: &mut BinarySerializer<S>) -> Result<()> {
         serializer.i16(*self)
     }
 }
 impl BinarySerialize for i32 {
-    fn binary_serialize(&self, serializer: &mut BinarySerializer) -> Result<()> {
+    fn binary_serialize<S: Sink>(&self, serializer: &mut BinarySerializer<S>) -> Result<()> {
         serializer.i32(*self)
     }
 }
 impl BinarySerialize for i64 {
-    fn binary_serialize(&self, serializer: &mut BinarySerializer) -> Result<()> {
+    fn binary_serialize<S: Sink>(&self, serializer: &mut BinarySerializer<S>) -> Result<()> {
         serializer.i64(*self)
     }
 }
 impl BinarySerialize for i128 {
-    fn binary_serialize(&self, serializer: &mut BinarySerializer) -> Result<()> {
+    fn binary_serialize<S: Sink>(&self, serializer: &mut BinarySerializer<S>) -> Result<()> {
         serializer.i128(*self)
     }
 }
 impl BinarySerialize for u8 {
-    fn binary_serialize(&self, serializer: &mut BinarySerializer) -> Result<()> {
+    fn binary_serialize<S: Sink>(&self, serializer: &mut BinarySerializer<S>) -> Result<()> {
         serializer.u8(*self)
     }
 }
 impl BinarySerialize for u16 {
-    fn binary_serialize(&self, serializer: &mut BinarySerializer) -> Result<()> {
+    fn binary_serialize<S: Sink>(&self, serializer: &mut BinarySerializer<S>) -> Result<()> {
         serializer.u16(*self)
     }
 }
 impl BinarySerialize for u32 {
-    fn binary_serialize(&self, serializer: &mut BinarySerializer) -> Result<()> {
+    fn binary_serialize<S: Sink>(&self, serializer: &mut BinarySerializer<S>) -> Result<()> {
         serializer.u32(*self)
     }
 }
 
 impl BinarySerialize for u64 {
-    fn binary_serialize(&self, serializer: &mut BinarySerializer) -> Result<()> {
+    fn binary_serialize<S: Sink>(&self, serializer: &mut BinarySerializer<S>) -> Result<()> {
         serializer.u64(*self)
     }
 }
 
 impl BinarySerialize for u128 {
-    fn binary_serialize(&self, serializer: &mut BinarySerializer) -> Result<()> {
+    fn binary_serialize<S: Sink>(&self, serializer: &mut BinarySerializer<S>) -> Result<()> {
         serializer.u128(*self)
     }
 }
 
 impl BinarySerialize for f32 {
-    fn binary_serialize(&self, serializer: &mut BinarySerializer) -> Result<()> {
+    fn binary_serialize<S: Sink>(&self, serializer: &mut BinarySerializer<S>) -> Result<()> {
         serializer.f32(*self)
     }
 }
 
 impl BinarySerialize for f64 {
-    fn binary_serialize(&self, serializer: &mut BinarySerializer) -> Result<()> {
+    fn binary_serialize<S: Sink>(&self, serializer: &mut BinarySerializer<S>) -> Result<()> {
         serializer.f64(*self)
     }
 }
 
 impl BinarySerialize for char {
-    fn binary_serialize(&self, serializer: &mut BinarySerializer) -> Result<()> {
+    fn binary_serialize<S: Sink>(&self, serializer: &mut BinarySerializer<S>) -> Result<()> {
         serializer.char(*self)
     }
 }
 
 impl BinarySerialize for String {
-    fn binary_serialize(&self, serializer: &mut BinarySerializer) -> Result<()> {
+    fn binary_serialize<S: Sink>(&self, serializer: &mut BinarySerializer<S>) -> Result<()> {
         serializer.str(self)
     }
 }
 
 impl BinarySerialize for &str {
-    fn binary_serialize(&self, serializer: &mut BinarySerializer) -> Result<()> {
+    fn binary_serialize<S: Sink>(&self, serializer: &mut BinarySerializer<S>) -> Result<()> {
         serializer.str(self)
     }
 }
@@ -110,7 +115,7 @@ impl<T> BinarySerialize for Option<T>
 where
     T: BinarySerialize,
 {
-    fn binary_serialize(&self, serializer: &mut BinarySerializer) -> Result<()> {
+    fn binary_serialize<S: Sink>(&self, serializer: &mut BinarySerializer<S>) -> Result<()> {
         match serializer.config().optional_strategy {
             crate::config::OptionalStrategy::Tagged => {
                 if let Some(v) = self {
@@ -134,14 +139,16 @@ impl<T> BinarySerialize for Vec<T>
 where
     T: BinarySerialize,
 {
-    fn binary_serialize(&self, serializer: &mut BinarySerializer) -> Result<()> {
-        serializer.container_length(self.len());
+    fn binary_serialize<S: Sink>(&self, serializer: &mut BinarySerializer<S>) -> Result<()> {
+        serializer.nested(|serializer| {
+            serializer.container_length(self.len())?;
 
-        for item in self {
-            item.binary_serialize(serializer)?;
-        }
+            for item in self {
+                item.binary_serialize(serializer)?;
+            }
 
-        Ok(())
+            Ok(())
+        })
     }
 }
 
@@ -149,7 +156,7 @@ impl<T> BinarySerialize for &[T]
 where
     T: BinarySerialize,
 {
-    fn binary_serialize(&self, serializer: &mut BinarySerializer) -> Result<()> {
+    fn binary_serialize<S: Sink>(&self, serializer: &mut BinarySerializer<S>) -> Result<()> {
         for item in *self {
             item.binary_serialize(serializer)?;
         }
@@ -161,7 +168,7 @@ impl<T, const N: usize> BinarySerialize for [T; N]
 where
     T: BinarySerialize,
 {
-    fn binary_serialize(&self, serializer: &mut BinarySerializer) -> Result<()> {
+    fn binary_serialize<S: Sink>(&self, serializer: &mut BinarySerializer<S>) -> Result<()> {
         for item in self {
             item.binary_serialize(serializer)?;
         }
@@ -169,59 +176,69 @@ where
     }
 }
 
+#[cfg(feature = "std")]
 impl<K, V> BinarySerialize for std::collections::HashMap<K, V>
 where
     K: BinarySerialize,
     V: BinarySerialize,
 {
-    fn binary_serialize(&self, serializer: &mut BinarySerializer) -> Result<()> {
-        serializer.container_length(self.len());
-        for (key, value) in self {
-            key.binary_serialize(serializer)?;
-            value.binary_serialize(serializer)?;
-        }
-        Ok(())
+    fn binary_serialize<S: Sink>(&self, serializer: &mut BinarySerializer<S>) -> Result<()> {
+        serializer.nested(|serializer| {
+            serializer.container_length(self.len())?;
+            for (key, value) in self {
+                key.binary_serialize(serializer)?;
+                value.binary_serialize(serializer)?;
+            }
+            Ok(())
+        })
     }
 }
 
+#[cfg(feature = "std")]
 impl<T> BinarySerialize for std::collections::HashSet<T>
 where
     T: BinarySerialize,
 {
-    fn binary_serialize(&self, serializer: &mut BinarySerializer) -> Result<()> {
-        serializer.container_length(self.len());
-        for item in self {
-            item.binary_serialize(serializer)?;
-        }
-        Ok(())
+    fn binary_serialize<S: Sink>(&self, serializer: &mut BinarySerializer<S>) -> Result<()> {
+        serializer.nested(|serializer| {
+            serializer.container_length(self.len())?;
+            for item in self {
+                item.binary_serialize(serializer)?;
+            }
+            Ok(())
+        })
     }
 }
 
-impl<K, V> BinarySerialize for std::collections::BTreeMap<K, V>
+impl<K, V> BinarySerialize for alloc::collections::BTreeMap<K, V>
 where
     K: BinarySerialize,
     V: BinarySerialize,
 {
-    fn binary_serialize(&self, serializer: &mut BinarySerializer) -> Result<()> {
-        serializer.container_length(self.len());
-        for (key, value) in self {
-            key.binary_serialize(serializer)?;
-            value.binary_serialize(serializer)?;
-        }
-        Ok(())
+    fn binary_serialize<S: Sink>(&self, serializer: &mut BinarySerializer<S>) -> Result<()> {
+        serializer.nested(|serializer| {
+            serializer.container_length(self.len())?;
+            for (key, value) in self {
+                key.binary_serialize(serializer)?;
+                value.binary_serialize(serializer)?;
+            }
+            Ok(())
+        })
     }
 }
 
-impl<T> BinarySerialize for std::collections::BTreeSet<T>
+impl<T> BinarySerialize for alloc::collections::BTreeSet<T>
 where
     T: BinarySerialize,
 {
-    fn binary_serialize(&self, serializer: &mut BinarySerializer) -> Result<()> {
-        serializer.container_length(self.len());
-        for item in self {
-            item.binary_serialize(serializer)?;
-        }
-        Ok(())
+    fn binary_serialize<S: Sink>(&self, serializer: &mut BinarySerializer<S>) -> Result<()> {
+        serializer.nested(|serializer| {
+            serializer.container_length(self.len())?;
+            for item in self {
+                item.binary_serialize(serializer)?;
+            }
+            Ok(())
+        })
     }
 }
 
@@ -232,9 +249,9 @@ macro_rules! impl_binary_serialize_for_tuple {
         where
             $($name: BinarySerialize),+
         {
-            fn binary_serialize(
+            fn binary_serialize<S: Sink>(
                 &self,
-                serializer: &mut BinarySerializer,
+                serializer: &mut BinarySerializer<S>,
             ) -> Result<()> {
                 let ($($name,)+) = self;
                 $(
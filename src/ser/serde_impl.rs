@@ -5,9 +5,12 @@ use crate::{
     error::{Error, Result},
 };
 
-use super::BinarySerializer;
+use super::{BinarySerializer, sink::Sink};
 
-impl serde::ser::Serializer for &mut BinarySerializer {
+impl<S> serde::ser::Serializer for &mut BinarySerializer<S>
+where
+    S: Sink,
+{
     type Ok = ();
 
     type Error = Error;
@@ -96,7 +99,7 @@ impl serde::ser::Serializer for &mut BinarySerializer {
 
     fn serialize_none(self) -> Result<Self::Ok> {
         if self.config().optional_strategy == OptionalStrategy::Tagged {
-            self.u8(0);
+            self.u8(0)?;
         }
         self.check_limit()
     }
@@ -106,7 +109,7 @@ impl serde::ser::Serializer for &mut BinarySerializer {
         T: ?Sized + serde::Serialize,
     {
         if self.config().optional_strategy == OptionalStrategy::Tagged {
-            self.u8(1);
+            self.u8(1)?;
         }
         value.serialize(self)
     }
@@ -135,7 +138,7 @@ impl serde::ser::Serializer for &mut BinarySerializer {
         T: ?Sized + serde::Serialize,
     {
         let _ = name;
-        value.serialize(self)
+        self.nested(|serializer| value.serialize(serializer))
     }
 
     fn serialize_newtype_variant<T>(
@@ -150,19 +153,23 @@ impl serde::ser::Serializer for &mut BinarySerializer {
     {
         let _ = name;
         let _ = variant;
-        self.u32(variant_index)?;
-        value.serialize(self)
+        self.nested(|serializer| {
+            serializer.u32(variant_index)?;
+            value.serialize(serializer)
+        })
     }
 
     fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq> {
+        self.enter()?;
         if let Some(len) = len {
-            self.container_length(len);
+            self.container_length(len)?;
         }
         Ok(self)
     }
 
     fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple> {
         let _ = len;
+        self.enter()?;
         Ok(self)
     }
 
@@ -173,6 +180,7 @@ impl serde::ser::Serializer for &mut BinarySerializer {
     ) -> Result<Self::SerializeTupleStruct> {
         let _ = len;
         let _ = name;
+        self.enter()?;
         Ok(self)
     }
 
@@ -186,13 +194,15 @@ impl serde::ser::Serializer for &mut BinarySerializer {
         let _ = len;
         let _ = variant;
         let _ = name;
+        self.enter()?;
         self.u32(variant_index)?;
         Ok(self)
     }
 
     fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap> {
+        self.enter()?;
         if let Some(len) = len {
-            self.container_length(len);
+            self.container_length(len)?;
         }
         Ok(self)
     }
@@ -200,6 +210,7 @@ impl serde::ser::Serializer for &mut BinarySerializer {
     fn serialize_struct(self, name: &'static str, len: usize) -> Result<Self::SerializeStruct> {
         let _ = len;
         let _ = name;
+        self.enter()?;
         Ok(self)
     }
 
@@ -213,6 +224,7 @@ impl serde::ser::Serializer for &mut BinarySerializer {
         let _ = len;
         let _ = variant;
         let _ = name;
+        self.enter()?;
         self.u32(variant_index)?;
         Ok(self)
     }
@@ -225,7 +237,10 @@ impl serde::ser::Serializer for &mut BinarySerializer {
 //
 // This impl is SerializeSeq so these methods are called after `serialize_seq`
 // is called on the Serializer.
-impl ser::SerializeSeq for &mut BinarySerializer {
+impl<S> ser::SerializeSeq for &mut BinarySerializer<S>
+where
+    S: Sink,
+{
     // Must match the `Ok` type of the serializer.
     type Ok = ();
     // Must match the `Error` type of the serializer.
@@ -241,12 +256,16 @@ impl ser::SerializeSeq for &mut BinarySerializer {
 
     // Close the sequence.
     fn end(self) -> Result<Self::Ok> {
+        self.leave();
         self.check_limit()
     }
 }
 
 // Same thing but for tuples.
-impl ser::SerializeTuple for &mut BinarySerializer {
+impl<S> ser::SerializeTuple for &mut BinarySerializer<S>
+where
+    S: Sink,
+{
     type Ok = ();
     type Error = Error;
 
@@ -258,12 +277,16 @@ impl ser::SerializeTuple for &mut BinarySerializer {
     }
 
     fn end(self) -> Result<Self::Ok> {
+        self.leave();
         self.check_limit()
     }
 }
 
 // Same thing but for tuple structs.
-impl ser::SerializeTupleStruct for &mut BinarySerializer {
+impl<S> ser::SerializeTupleStruct for &mut BinarySerializer<S>
+where
+    S: Sink,
+{
     type Ok = ();
     type Error = Error;
 
@@ -275,6 +298,7 @@ impl ser::SerializeTupleStruct for &mut BinarySerializer {
     }
 
     fn end(self) -> Result<Self::Ok> {
+        self.leave();
         self.check_limit()
     }
 }
@@ -288,7 +312,10 @@ impl ser::SerializeTupleStruct for &mut BinarySerializer {
 //
 // So the `end` method in this impl is responsible for closing both the `]` and
 // the `}`.
-impl ser::SerializeTupleVariant for &mut BinarySerializer {
+impl<S> ser::SerializeTupleVariant for &mut BinarySerializer<S>
+where
+    S: Sink,
+{
     type Ok = ();
     type Error = Error;
 
@@ -300,6 +327,7 @@ impl ser::SerializeTupleVariant for &mut BinarySerializer {
     }
 
     fn end(self) -> Result<Self::Ok> {
+        self.leave();
         self.check_limit()
     }
 }
@@ -312,7 +340,10 @@ impl ser::SerializeTupleVariant for &mut BinarySerializer {
 // `serialize_entry` method allows serializers to optimize for the case where
 // key and value are both available simultaneously. In JSON it doesn't make a
 // difference so the default behavior for `serialize_entry` is fine.
-impl ser::SerializeMap for &mut BinarySerializer {
+impl<S> ser::SerializeMap for &mut BinarySerializer<S>
+where
+    S: Sink,
+{
     type Ok = ();
     type Error = Error;
 
@@ -342,13 +373,17 @@ impl ser::SerializeMap for &mut BinarySerializer {
     }
 
     fn end(self) -> Result<Self::Ok> {
+        self.leave();
         self.check_limit()
     }
 }
 
 // Structs are like maps in which the keys are constrained to be compile-time
 // constant strings.
-impl ser::SerializeStruct for &mut BinarySerializer {
+impl<S> ser::SerializeStruct for &mut BinarySerializer<S>
+where
+    S: Sink,
+{
     type Ok = ();
     type Error = Error;
 
@@ -360,13 +395,17 @@ impl ser::SerializeStruct for &mut BinarySerializer {
     }
 
     fn end(self) -> Result<Self::Ok> {
+        self.leave();
         self.check_limit()
     }
 }
 
 // Similar to `SerializeTupleVariant`, here the `end` method is responsible for
 // closing both of the curly braces opened by `serialize_struct_variant`.
-impl ser::SerializeStructVariant for &mut BinarySerializer {
+impl<S> ser::SerializeStructVariant for &mut BinarySerializer<S>
+where
+    S: Sink,
+{
     type Ok = ();
     type Error = Error;
 
@@ -379,6 +418,7 @@ impl ser::SerializeStructVariant for &mut BinarySerializer {
     }
 
     fn end(self) -> Result<Self::Ok> {
+        self.leave();
         self.check_limit()
     }
 }
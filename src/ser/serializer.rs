@@ -1,29 +1,117 @@
-use bytes::{BufMut, BytesMut};
+use alloc::string::ToString;
+
+use bytes::BytesMut;
 
 use crate::{
-    config::{Config, ContainerLengthStrategy, EndiannessStrategy},
+    config::{Config, ContainerLengthStrategy, EndiannessStrategy, IntegerEncodingStrategy},
     error::{Error, Result},
 };
 
+use super::{
+    BinarySerialize,
+    sink::{Sink, SizeSink},
+};
+
 #[derive(Debug, Default)]
-pub struct BinarySerializer {
-    // Buffer to store the serialized binary output
-    output: BytesMut,
+pub struct BinarySerializer<S = BytesMut> {
     // Configuration for serialization (e.g., endianness, optional strategy, etc.)
     config: Config,
+    // Number of bytes written to the sink so far
+    size: usize,
+    // Current nesting level of containers, structs and enums
+    depth: usize,
+    // Sink receiving the serialized binary output
+    output: S,
+}
+
+/// Bytes set aside in the output, to be filled in once their value is known.
+///
+/// Created by [`BinarySerializer::reserve`], and given back to
+/// [`BinarySerializer::patch`].
+#[derive(Debug)]
+#[must_use = "a reservation must be patched"]
+pub struct Reservation {
+    position: usize,
+    len: usize,
+}
+
+impl Reservation {
+    /// Returns the offset of the reserved bytes in the output.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Returns the number of reserved bytes.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Checks if no bytes are reserved.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+/// A [`Sink`] overwriting the bytes another sink holds from `start` on, as a
+/// [`Reservation`] is patched, so no buffer is needed.
+struct PatchSink<'a, S> {
+    output: &'a mut S,
+    start: usize,
+    written: usize,
+}
+
+impl<S> Sink for PatchSink<'_, S>
+where
+    S: Sink,
+{
+    fn write(&mut self, bytes: &[u8]) -> Result<()> {
+        self.output.patch(self.start + self.written, bytes)?;
+        self.written += bytes.len();
+        Ok(())
+    }
+
+    fn patch(&mut self, position: usize, bytes: &[u8]) -> Result<()> {
+        self.output.patch(self.start + position, bytes)
+    }
 }
 
 impl BinarySerializer {
     /// Creates a new `BinarySerializer` with the specified configuration.
     pub fn new(config: Config) -> Self {
+        Self::with_sink(BytesMut::new(), config)
+    }
+
+    /// Consumes the serializer and returns the serialized output as `BytesMut`.
+    pub fn output(self) -> BytesMut {
+        self.output
+    }
+}
+
+impl<S> BinarySerializer<S>
+where
+    S: Sink,
+{
+    /// Creates a new `BinarySerializer` writing into the given sink.
+    pub fn with_sink(output: S, config: Config) -> Self {
         Self {
-            output: BytesMut::new(),
             config,
+            size: 0,
+            depth: 0,
+            output,
         }
     }
 
-    /// Consumes the serializer and returns the serialized output as `BytesMut`.
-    pub fn output(self) -> BytesMut {
+    /// Creates a `BinarySerializer` writing to `output` as if `position` bytes were
+    /// already written, so alignment and padding line up with a parser at `position`.
+    pub(crate) fn with_sink_at(output: S, config: Config, position: usize) -> Self {
+        Self {
+            size: position,
+            ..Self::with_sink(output, config)
+        }
+    }
+
+    /// Consumes the serializer and returns the underlying sink.
+    pub fn into_sink(self) -> S {
         self.output
     }
 
@@ -32,175 +120,384 @@ impl BinarySerializer {
         &self.config
     }
 
-    /// Returns a mutable reference to the output buffer.
+    /// Runs `f` with `config` in place of the current configuration, restoring the
+    /// previous configuration afterwards, even if `f` fails.
+    ///
+    /// This is how a single value can be written with different settings than its
+    /// surroundings (e.g., a varint field inside a fixed-width struct).
+    pub fn scoped_config<R>(
+        &mut self,
+        config: Config,
+        f: impl FnOnce(&mut Self) -> Result<R>,
+    ) -> Result<R> {
+        let previous = core::mem::replace(&mut self.config, config);
+        let result = f(self);
+        self.config = previous;
+        result
+    }
+
+    /// Runs `f` one nesting level deeper, failing with [`Error::DepthLimitExceeded`]
+    /// if that goes past the configured maximum depth.
+    ///
+    /// Containers, structs and enums write their contents through this, so recursive
+    /// types can't nest without bound.
+    pub fn nested<R>(&mut self, f: impl FnOnce(&mut Self) -> Result<R>) -> Result<R> {
+        self.enter()?;
+        let result = f(self);
+        self.leave();
+        result
+    }
+
+    /// Goes one nesting level deeper, checking the configured maximum depth.
+    pub(crate) fn enter(&mut self) -> Result<()> {
+        if let Some(max_depth) = self.config.max_depth
+            && self.depth >= max_depth
+        {
+            return Err(Error::DepthLimitExceeded { max_depth });
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    /// Goes back up one nesting level.
+    pub(crate) fn leave(&mut self) {
+        self.depth -= 1;
+    }
+
+    /// Returns the number of bytes written so far.
     pub fn size(&self) -> usize {
-        self.output.len()
+        self.size
     }
 
-    /// Checks if the output buffer is empty.
+    /// Checks if nothing has been written yet.
     pub fn is_empty(&self) -> bool {
-        self.output.is_empty()
+        self.size == 0
     }
 
     /// Checks if the serialized output exceeds the configured size limit.
     /// Returns an error if the limit is exceeded.
     pub fn check_limit(&self) -> Result<()> {
-        if let Some(limit) = self.config.limit {
-            if self.output.len() > limit {
-                return Err(Error::LimitExceeded {
-                    limit,
-                    size: self.output.len(),
-                });
-            }
+        if let Some(limit) = self.config.limit
+            && self.size > limit
+        {
+            return Err(Error::LimitExceeded {
+                limit,
+                size: self.size,
+            });
+        }
+        Ok(())
+    }
+
+    /// Writes raw bytes to the sink, refusing to go past the configured size limit.
+    fn write(&mut self, bytes: &[u8]) -> Result<()> {
+        let size = self.size + bytes.len();
+        if let Some(limit) = self.config.limit
+            && size > limit
+        {
+            return Err(Error::LimitExceeded { limit, size });
+        }
+
+        self.output.write(bytes)?;
+        self.size = size;
+        Ok(())
+    }
+
+    /// Writes `len` bytes holding `byte`, from a stack buffer so no allocation is needed.
+    fn write_repeated(&mut self, byte: u8, len: usize) -> Result<()> {
+        let size = self.size + len;
+        if let Some(limit) = self.config.limit
+            && size > limit
+        {
+            return Err(Error::LimitExceeded { limit, size });
+        }
+
+        let chunk = [byte; 64];
+        let mut remaining = len;
+        while remaining > 0 {
+            let written = remaining.min(chunk.len());
+            self.write(&chunk[..written])?;
+            remaining -= written;
         }
         Ok(())
     }
 
+    /// Writes `len` padding bytes holding `fill`.
+    pub fn pad(&mut self, len: usize, fill: u8) -> Result<()> {
+        self.write_repeated(fill, len)
+    }
+
+    /// Writes padding bytes holding `fill` up to the next position that is a multiple
+    /// of `align`.
+    pub fn align(&mut self, align: usize, fill: u8) -> Result<()> {
+        self.pad(self.size.next_multiple_of(align) - self.size, fill)
+    }
+
+    /// Sets aside the next `len` bytes of the output, so they can be written later with
+    /// [`patch`](Self::patch).
+    ///
+    /// This is how a header can hold the length of, or an offset into, what follows it
+    /// without serializing twice. Until the reservation is patched, writer-backed
+    /// serializers keep the bytes written in memory.
+    pub fn reserve(&mut self, len: usize) -> Result<Reservation> {
+        let position = self.size;
+        self.output.retain();
+        if let Err(error) = self.write_repeated(0, len) {
+            self.output.release()?;
+            return Err(error);
+        }
+        Ok(Reservation { position, len })
+    }
+
+    /// Fills `reservation` with `value`, serialized with the current configuration.
+    ///
+    /// Fails with [`Error::InvalidLength`] if `value` doesn't take exactly the
+    /// reserved number of bytes.
+    pub fn patch<T>(&mut self, reservation: Reservation, value: &T) -> Result<()>
+    where
+        T: BinarySerialize + ?Sized,
+    {
+        let result = self.encode_patch(&reservation, value);
+        let released = self.output.release();
+        result?;
+        released
+    }
+
+    fn encode_patch<T>(&mut self, reservation: &Reservation, value: &T) -> Result<()>
+    where
+        T: BinarySerialize + ?Sized,
+    {
+        // the value is sized first, so a wrong size leaves the reserved bytes untouched
+        let config = self.config.with_no_limit();
+        let mut sizer = BinarySerializer::with_sink(SizeSink::new(), config);
+        sizer.depth = self.depth;
+        value.binary_serialize(&mut sizer)?;
+        if sizer.size != reservation.len {
+            return Err(Error::InvalidLength {
+                expected: alloc::format!("{} bytes", reservation.len),
+                found: alloc::format!("{} bytes", sizer.size),
+            });
+        }
+
+        let sink = PatchSink {
+            output: &mut self.output,
+            start: reservation.position,
+            written: 0,
+        };
+        let mut serializer = BinarySerializer::with_sink(sink, config);
+        serializer.depth = self.depth;
+        value.binary_serialize(&mut serializer)
+    }
+
     /// Writes the length of a container (e.g., sequence, string) to the output buffer
     /// based on the configured endianness and container length strategy.
-    pub fn container_length(&mut self, length: usize) {
+    ///
+    /// Fails with [`Error::Overflow`] if the length doesn't fit the configured width.
+    pub fn container_length(&mut self, length: usize) -> Result<()> {
+        let max = match self.config.container_length_strategy {
+            ContainerLengthStrategy::OneByte => u8::MAX as u128,
+            ContainerLengthStrategy::TwoBytes => u16::MAX as u128,
+            ContainerLengthStrategy::FourBytes => u32::MAX as u128,
+            ContainerLengthStrategy::EightBytes => u64::MAX as u128,
+            ContainerLengthStrategy::SixteenBytes | ContainerLengthStrategy::Varint => u128::MAX,
+        };
+        check_length(length, max)?;
+
         match (
             self.config.endianness_strategy,
             self.config.container_length_strategy,
         ) {
-            (_, ContainerLengthStrategy::OneByte) => {
-                self.output.put_u8(length as u8);
-            }
+            (_, ContainerLengthStrategy::OneByte) => self.write(&[length as u8]),
             (EndiannessStrategy::Big, ContainerLengthStrategy::TwoBytes) => {
-                self.output.put_u16(length as u16);
+                self.write(&(length as u16).to_be_bytes())
             }
             (EndiannessStrategy::Little, ContainerLengthStrategy::TwoBytes) => {
-                self.output.put_u16_le(length as u16);
+                self.write(&(length as u16).to_le_bytes())
             }
             (EndiannessStrategy::Big, ContainerLengthStrategy::FourBytes) => {
-                self.output.put_u32(length as u32);
+                self.write(&(length as u32).to_be_bytes())
             }
             (EndiannessStrategy::Little, ContainerLengthStrategy::FourBytes) => {
-                self.output.put_u32_le(length as u32);
+                self.write(&(length as u32).to_le_bytes())
             }
             (EndiannessStrategy::Big, ContainerLengthStrategy::EightBytes) => {
-                self.output.put_u64(length as u64);
+                self.write(&(length as u64).to_be_bytes())
             }
             (EndiannessStrategy::Little, ContainerLengthStrategy::EightBytes) => {
-                self.output.put_u64_le(length as u64);
+                self.write(&(length as u64).to_le_bytes())
             }
             (EndiannessStrategy::Big, ContainerLengthStrategy::SixteenBytes) => {
-                self.output.put_u128(length as u128);
+                self.write(&(length as u128).to_be_bytes())
             }
             (EndiannessStrategy::Little, ContainerLengthStrategy::SixteenBytes) => {
-                self.output.put_u128_le(length as u128);
+                self.write(&(length as u128).to_le_bytes())
             }
+            (_, ContainerLengthStrategy::Varint) => self.write_varint(length as u128),
         }
     }
 
+    /// Writes an unsigned LEB128 varint: 7 bits per byte, least significant group first,
+    /// with the high bit set on every byte but the last.
+    fn write_varint(&mut self, mut value: u128) -> Result<()> {
+        // a u128 takes at most 19 groups of 7 bits
+        let mut buf = [0u8; 19];
+        let mut len = 0;
+        loop {
+            let byte = (value & 0x7f) as u8;
+            value >>= 7;
+            if value == 0 {
+                buf[len] = byte;
+                len += 1;
+                break;
+            }
+            buf[len] = byte | 0x80;
+            len += 1;
+        }
+        self.write(&buf[..len])
+    }
+
     pub fn bool(&mut self, v: bool) -> Result<()> {
-        self.output.put_u8(if v { 1 } else { 0 });
-        self.check_limit()
+        self.write(&[if v { 1 } else { 0 }])
     }
 
     pub fn i8(&mut self, v: i8) -> Result<()> {
-        self.output.put_i8(v);
-        self.check_limit()
+        self.write(&v.to_le_bytes())
     }
 
     pub fn i16(&mut self, v: i16) -> Result<()> {
-        match self.config.endianness_strategy {
-            EndiannessStrategy::Big => self.output.put_i16(v),
-            EndiannessStrategy::Little => self.output.put_i16_le(v),
+        match (
+            self.config.integer_encoding_strategy,
+            self.config.endianness_strategy,
+        ) {
+            (IntegerEncodingStrategy::Varint, _) => {
+                self.write_varint(((v << 1) ^ (v >> 15)) as u16 as u128)
+            }
+            (_, EndiannessStrategy::Big) => self.write(&v.to_be_bytes()),
+            (_, EndiannessStrategy::Little) => self.write(&v.to_le_bytes()),
         }
-        self.check_limit()
     }
 
     pub fn i32(&mut self, v: i32) -> Result<()> {
-        match self.config.endianness_strategy {
-            EndiannessStrategy::Big => self.output.put_i32(v),
-            EndiannessStrategy::Little => self.output.put_i32_le(v),
+        match (
+            self.config.integer_encoding_strategy,
+            self.config.endianness_strategy,
+        ) {
+            (IntegerEncodingStrategy::Varint, _) => {
+                self.write_varint(((v << 1) ^ (v >> 31)) as u32 as u128)
+            }
+            (_, EndiannessStrategy::Big) => self.write(&v.to_be_bytes()),
+            (_, EndiannessStrategy::Little) => self.write(&v.to_le_bytes()),
         }
-        self.check_limit()
     }
 
     pub fn i64(&mut self, v: i64) -> Result<()> {
-        match self.config.endianness_strategy {
-            EndiannessStrategy::Big => self.output.put_i64(v),
-            EndiannessStrategy::Little => self.output.put_i64_le(v),
+        match (
+            self.config.integer_encoding_strategy,
+            self.config.endianness_strategy,
+        ) {
+            (IntegerEncodingStrategy::Varint, _) => {
+                self.write_varint(((v << 1) ^ (v >> 63)) as u64 as u128)
+            }
+            (_, EndiannessStrategy::Big) => self.write(&v.to_be_bytes()),
+            (_, EndiannessStrategy::Little) => self.write(&v.to_le_bytes()),
         }
-        self.check_limit()
     }
 
     pub fn i128(&mut self, v: i128) -> Result<()> {
-        match self.config.endianness_strategy {
-            EndiannessStrategy::Big => self.output.put_i128(v),
-            EndiannessStrategy::Little => self.output.put_i128_le(v),
+        match (
+            self.config.integer_encoding_strategy,
+            self.config.endianness_strategy,
+        ) {
+            (IntegerEncodingStrategy::Varint, _) => {
+                self.write_varint(((v << 1) ^ (v >> 127)) as u128)
+            }
+            (_, EndiannessStrategy::Big) => self.write(&v.to_be_bytes()),
+            (_, EndiannessStrategy::Little) => self.write(&v.to_le_bytes()),
         }
-        self.check_limit()
     }
 
     pub fn u8(&mut self, v: u8) -> Result<()> {
-        self.output.put_u8(v);
-        self.check_limit()
+        self.write(&[v])
     }
 
     pub fn u16(&mut self, v: u16) -> Result<()> {
-        match self.config.endianness_strategy {
-            EndiannessStrategy::Big => self.output.put_u16(v),
-            EndiannessStrategy::Little => self.output.put_u16_le(v),
+        match (
+            self.config.integer_encoding_strategy,
+            self.config.endianness_strategy,
+        ) {
+            (IntegerEncodingStrategy::Varint, _) => self.write_varint(v as u128),
+            (_, EndiannessStrategy::Big) => self.write(&v.to_be_bytes()),
+            (_, EndiannessStrategy::Little) => self.write(&v.to_le_bytes()),
         }
-        self.check_limit()
     }
 
     pub fn u32(&mut self, v: u32) -> Result<()> {
-        match self.config.endianness_strategy {
-            EndiannessStrategy::Big => self.output.put_u32(v),
-            EndiannessStrategy::Little => self.output.put_u32_le(v),
+        match (
+            self.config.integer_encoding_strategy,
+            self.config.endianness_strategy,
+        ) {
+            (IntegerEncodingStrategy::Varint, _) => self.write_varint(v as u128),
+            (_, EndiannessStrategy::Big) => self.write(&v.to_be_bytes()),
+            (_, EndiannessStrategy::Little) => self.write(&v.to_le_bytes()),
         }
-        self.check_limit()
     }
 
     pub fn u64(&mut self, v: u64) -> Result<()> {
-        match self.config.endianness_strategy {
-            EndiannessStrategy::Big => self.output.put_u64(v),
-            EndiannessStrategy::Little => self.output.put_u64_le(v),
+        match (
+            self.config.integer_encoding_strategy,
+            self.config.endianness_strategy,
+        ) {
+            (IntegerEncodingStrategy::Varint, _) => self.write_varint(v as u128),
+            (_, EndiannessStrategy::Big) => self.write(&v.to_be_bytes()),
+            (_, EndiannessStrategy::Little) => self.write(&v.to_le_bytes()),
         }
-        self.check_limit()
     }
 
     pub fn u128(&mut self, v: u128) -> Result<()> {
-        match self.config.endianness_strategy {
-            EndiannessStrategy::Big => self.output.put_u128(v),
-            EndiannessStrategy::Little => self.output.put_u128_le(v),
+        match (
+            self.config.integer_encoding_strategy,
+            self.config.endianness_strategy,
+        ) {
+            (IntegerEncodingStrategy::Varint, _) => self.write_varint(v),
+            (_, EndiannessStrategy::Big) => self.write(&v.to_be_bytes()),
+            (_, EndiannessStrategy::Little) => self.write(&v.to_le_bytes()),
         }
-        self.check_limit()
     }
 
     pub fn f32(&mut self, v: f32) -> Result<()> {
         match self.config.endianness_strategy {
-            EndiannessStrategy::Big => self.output.put_f32(v),
-            EndiannessStrategy::Little => self.output.put_f32_le(v),
+            EndiannessStrategy::Big => self.write(&v.to_be_bytes()),
+            EndiannessStrategy::Little => self.write(&v.to_le_bytes()),
         }
-        self.check_limit()
     }
 
     pub fn f64(&mut self, v: f64) -> Result<()> {
         match self.config.endianness_strategy {
-            EndiannessStrategy::Big => self.output.put_f64(v),
-            EndiannessStrategy::Little => self.output.put_f64_le(v),
+            EndiannessStrategy::Big => self.write(&v.to_be_bytes()),
+            EndiannessStrategy::Little => self.write(&v.to_le_bytes()),
         }
-        self.check_limit()
     }
 
     pub fn char(&mut self, v: char) -> Result<()> {
-        self.output.put_slice(v.to_string().as_bytes());
-        self.check_limit()
+        self.write(v.encode_utf8(&mut [0; 4]).as_bytes())
     }
 
     pub fn str(&mut self, v: &str) -> Result<()> {
-        self.container_length(v.len());
-        self.output.put_slice(v.as_bytes());
-        self.check_limit()
+        self.container_length(v.len())?;
+        self.write(v.as_bytes())
     }
 
     pub fn bytes(&mut self, v: &[u8]) -> Result<()> {
-        self.output.put_slice(v);
-        self.check_limit()
+        self.write(v)
+    }
+}
+
+/// Checks that a container length fits in a length prefix holding at most `max`.
+pub(crate) fn check_length(length: usize, max: u128) -> Result<()> {
+    if length as u128 > max {
+        return Err(Error::Overflow {
+            value: length.to_string(),
+            max: max.to_string(),
+        });
     }
+    Ok(())
 }
@@ -0,0 +1,267 @@
+use alloc::string::String;
+#[cfg(feature = "std")]
+use alloc::vec::Vec;
+
+use bytes::{BufMut, BytesMut};
+
+use crate::error::{Error, Result};
+
+/// A destination for the bytes produced by a [`BinarySerializer`](crate::BinarySerializer).
+///
+/// Implement this trait to serialize into a custom output without going through an
+/// intermediate buffer.
+pub trait Sink {
+    /// Writes all of `bytes` to the sink.
+    fn write(&mut self, bytes: &[u8]) -> Result<()>;
+
+    /// Starts keeping the bytes written from now on, so they can still be patched.
+    ///
+    /// Calls nest: every `retain` is matched by a [`release`](Sink::release), and bytes
+    /// are kept while at least one is active. In-memory sinks keep everything anyway.
+    fn retain(&mut self) {}
+
+    /// Stops keeping the bytes written since the matching [`retain`](Sink::retain).
+    fn release(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Overwrites the bytes already written at `position` with `bytes`.
+    ///
+    /// Only bytes written since the oldest active [`retain`](Sink::retain) can be
+    /// patched. Sinks that can't go back fail with [`Error::Message`].
+    fn patch(&mut self, position: usize, bytes: &[u8]) -> Result<()> {
+        let _ = (position, bytes);
+        Err(Error::Message(String::from(
+            "this sink doesn't support patching",
+        )))
+    }
+
+    /// Checks that every byte written reached the sink's destination, once serializing
+    /// is done.
+    ///
+    /// Sinks holding bytes back for a [`retain`](Sink::retain) that was never released
+    /// fail, as those bytes would otherwise be silently lost.
+    fn finish(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl<S> Sink for &mut S
+where
+    S: Sink + ?Sized,
+{
+    fn write(&mut self, bytes: &[u8]) -> Result<()> {
+        (**self).write(bytes)
+    }
+
+    fn retain(&mut self) {
+        (**self).retain()
+    }
+
+    fn release(&mut self) -> Result<()> {
+        (**self).release()
+    }
+
+    fn patch(&mut self, position: usize, bytes: &[u8]) -> Result<()> {
+        (**self).patch(position, bytes)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        (**self).finish()
+    }
+}
+
+impl Sink for BytesMut {
+    fn write(&mut self, bytes: &[u8]) -> Result<()> {
+        self.put_slice(bytes);
+        Ok(())
+    }
+
+    fn patch(&mut self, position: usize, bytes: &[u8]) -> Result<()> {
+        self[position..position + bytes.len()].copy_from_slice(bytes);
+        Ok(())
+    }
+}
+
+/// A [`Sink`] that discards the bytes and only counts them.
+///
+/// Serializing into a `SizeSink` computes the exact encoded size of a value without
+/// allocating any output.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SizeSink {
+    size: usize,
+}
+
+impl SizeSink {
+    /// Creates a new `SizeSink` with a count of zero.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of bytes written so far.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+}
+
+impl Sink for SizeSink {
+    fn write(&mut self, bytes: &[u8]) -> Result<()> {
+        self.size += bytes.len();
+        Ok(())
+    }
+
+    fn patch(&mut self, position: usize, bytes: &[u8]) -> Result<()> {
+        let _ = (position, bytes);
+        Ok(())
+    }
+}
+
+/// A [`Sink`] writing into a caller-provided buffer, without any allocation.
+///
+/// Writing past the end of the buffer fails with [`Error::BufferFull`].
+#[derive(Debug)]
+pub struct SliceSink<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+}
+
+impl<'a> SliceSink<'a> {
+    /// Creates a new `SliceSink` writing from the start of `buf`.
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        Self { buf, len: 0 }
+    }
+
+    /// Returns the number of bytes written so far.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Checks if nothing has been written yet.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Consumes the sink and returns the written part of the buffer.
+    pub fn into_written(self) -> &'a mut [u8] {
+        &mut self.buf[..self.len]
+    }
+}
+
+impl Sink for SliceSink<'_> {
+    fn write(&mut self, bytes: &[u8]) -> Result<()> {
+        let end = self.len + bytes.len();
+        if end > self.buf.len() {
+            return Err(Error::BufferFull {
+                capacity: self.buf.len(),
+                required: end,
+            });
+        }
+
+        self.buf[self.len..end].copy_from_slice(bytes);
+        self.len = end;
+        Ok(())
+    }
+
+    fn patch(&mut self, position: usize, bytes: &[u8]) -> Result<()> {
+        self.buf[position..position + bytes.len()].copy_from_slice(bytes);
+        Ok(())
+    }
+}
+
+/// A [`Sink`] that streams bytes into any [`std::io::Write`] (a file, a socket, ...).
+///
+/// Bytes are handed to the writer as soon as they are produced, so nothing is
+/// accumulated in memory. The writer is not flushed.
+///
+/// While a serializer reservation is pending, the bytes written are kept in memory
+/// instead, and handed to the writer once it has been patched.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct IoSink<W> {
+    writer: W,
+    // bytes written since the oldest active `retain`, not handed to the writer yet
+    retained: Vec<u8>,
+    // number of bytes handed to the writer so far
+    flushed: usize,
+    // number of active `retain` calls
+    retaining: usize,
+}
+
+#[cfg(feature = "std")]
+impl<W> IoSink<W>
+where
+    W: std::io::Write,
+{
+    /// Creates a new `IoSink` writing into `writer`.
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            retained: Vec::new(),
+            flushed: 0,
+            retaining: 0,
+        }
+    }
+
+    /// Returns a reference to the underlying writer.
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// Consumes the sink and returns the underlying writer.
+    ///
+    /// Bytes kept for a reservation that was never patched are lost, see
+    /// [`finish`](Sink::finish).
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W> Sink for IoSink<W>
+where
+    W: std::io::Write,
+{
+    fn write(&mut self, bytes: &[u8]) -> Result<()> {
+        if self.retaining > 0 {
+            self.retained.extend_from_slice(bytes);
+            return Ok(());
+        }
+
+        self.writer.write_all(bytes)?;
+        self.flushed += bytes.len();
+        Ok(())
+    }
+
+    fn retain(&mut self) {
+        self.retaining += 1;
+    }
+
+    fn release(&mut self) -> Result<()> {
+        self.retaining -= 1;
+        if self.retaining == 0 {
+            self.writer.write_all(&self.retained)?;
+            self.flushed += self.retained.len();
+            self.retained.clear();
+        }
+        Ok(())
+    }
+
+    fn patch(&mut self, position: usize, bytes: &[u8]) -> Result<()> {
+        let start = position.checked_sub(self.flushed).ok_or_else(|| {
+            Error::Message(String::from(
+                "can't patch bytes already handed to the writer",
+            ))
+        })?;
+        self.retained[start..start + bytes.len()].copy_from_slice(bytes);
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        if self.retaining > 0 {
+            return Err(Error::Message(String::from(
+                "a reservation was never patched, its bytes were not written",
+            )));
+        }
+        Ok(())
+    }
+}
@@ -99,6 +99,7 @@ mod parser_little_endian_tagged_optional {
     }
 
     #[test]
+    #[allow(clippy::unit_cmp)]
     fn unit() {
         let j = vec![];
         let expected = ();
@@ -146,6 +147,7 @@ mod parser_little_endian_tagged_optional {
     }
 
     #[test]
+    #[cfg(feature = "std")]
     fn map() {
         use std::collections::HashMap;
 
@@ -367,6 +369,7 @@ mod parser_big_endian_untagged_optional {
     }
 
     #[test]
+    #[allow(clippy::unit_cmp)]
     fn unit() {
         let j = vec![];
         let expected = ();
@@ -414,6 +417,7 @@ mod parser_big_endian_untagged_optional {
     }
 
     #[test]
+    #[cfg(feature = "std")]
     fn map() {
         use std::collections::HashMap;
 
@@ -517,3 +521,1682 @@ mod parser_big_endian_untagged_optional {
         assert_eq!(expected, from_bytes(&j).unwrap());
     }
 }
+
+#[cfg(all(test, feature = "std"))]
+mod parser_reader {
+    use std::io::{Cursor, Read};
+
+    use binja::{
+        BinaryParse,
+        error::{Error, PathSegment},
+        from_reader,
+    };
+
+    #[derive(BinaryParse, PartialEq, Debug)]
+    struct Test {
+        int: u32,
+        opt: Option<u32>,
+        seq: Vec<String>,
+    }
+
+    const BYTES: [u8; 23] = [
+        0x01, 0x00, 0x00, 0x00, // int
+        0x01, // tagged opt
+        0x02, 0x00, 0x00, 0x00, // tagged opt value
+        0x02, 0x00, 0x00, 0x00, // seq size
+        0x01, 0x00, 0x00, 0x00, // string size
+        b'a', // string
+        0x01, 0x00, 0x00, 0x00, // string size
+        b'b', // string
+    ];
+
+    // hands out a single byte per read call
+    struct Trickle<'a>(&'a [u8]);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            match (self.0.split_first(), buf.first_mut()) {
+                (Some((byte, rest)), Some(slot)) => {
+                    *slot = *byte;
+                    self.0 = rest;
+                    Ok(1)
+                }
+                _ => Ok(0),
+            }
+        }
+    }
+
+    fn expected() -> Test {
+        Test {
+            int: 1,
+            opt: Some(2),
+            seq: vec!["a".to_owned(), "b".to_owned()],
+        }
+    }
+
+    #[test]
+    fn test_struct() {
+        assert_eq!(expected(), from_reader(Trickle(&BYTES)).unwrap());
+    }
+
+    #[test]
+    fn stops_after_value() {
+        let mut input = BYTES.to_vec();
+        input.push(0xff);
+
+        let mut cursor = Cursor::new(input);
+        assert_eq!(expected(), from_reader(&mut cursor).unwrap());
+        assert_eq!(BYTES.len() as u64, cursor.position());
+    }
+
+    #[test]
+    fn truncated() {
+        assert_eq!(
+            Err(Error::Context {
+                path: vec![
+                    PathSegment::Type("Test"),
+                    PathSegment::Field("seq"),
+                    PathSegment::Index(1),
+                ],
+                offset: 22,
+                source: Box::new(Error::NoEnoughData {
+                    expected: 1,
+                    available: 0
+                }),
+            }),
+            from_reader::<Test, _>(Trickle(&BYTES[..BYTES.len() - 1]))
+        );
+        assert_eq!(
+            Err(Error::NoEnoughData {
+                expected: 4,
+                available: 2
+            }),
+            from_reader::<u32, _>(Trickle(&[0x01, 0x02]))
+        );
+    }
+}
+
+#[cfg(test)]
+mod parser_varint_container_size {
+    use binja::{config::Config, error::Error, from_bytes_with_config};
+
+    fn config() -> Config {
+        Config::default().with_varint_container_size()
+    }
+
+    #[test]
+    fn string() {
+        let j = vec![0x02, b'a', b'b'];
+        let expected = "ab".to_owned();
+        assert_eq!(
+            expected,
+            from_bytes_with_config::<String>(&j, config()).unwrap().0
+        );
+    }
+
+    #[test]
+    fn seq() {
+        let mut j = vec![0xac, 0x02];
+        j.extend([7u8; 300]);
+        let expected = vec![7u8; 300];
+        assert_eq!(
+            expected,
+            from_bytes_with_config::<Vec<u8>>(&j, config()).unwrap().0
+        );
+    }
+
+    #[test]
+    fn overlong() {
+        let j = vec![0x81, 0x00, b'a'];
+        assert_eq!(
+            Err(Error::InvalidVarint {
+                bytes: vec![0x81, 0x00]
+            }),
+            from_bytes_with_config::<String>(&j, config())
+        );
+    }
+
+    #[test]
+    fn overflow() {
+        let mut j = vec![0xff; usize::BITS.div_ceil(7) as usize];
+        assert!(matches!(
+            from_bytes_with_config::<Vec<u8>>(&j, config()),
+            Err(Error::InvalidVarint { .. })
+        ));
+
+        // the final group has more bits than a usize can hold
+        *j.last_mut().unwrap() = 0x7f;
+        assert!(matches!(
+            from_bytes_with_config::<Vec<u8>>(&j, config()),
+            Err(Error::InvalidVarint { .. })
+        ));
+    }
+
+    #[test]
+    fn truncated() {
+        let j = vec![0x80];
+        assert_eq!(
+            Err(Error::NoEnoughData {
+                expected: 1,
+                available: 0
+            }),
+            from_bytes_with_config::<Vec<u8>>(&j, config())
+        );
+    }
+}
+
+#[cfg(test)]
+mod parser_varint_encoding {
+    use binja::{
+        BinaryParse, config::Config, containers::ContainerU16, error::Error, from_bytes,
+        from_bytes_with_config,
+    };
+
+    fn config() -> Config {
+        Config::default().with_varint_encoding()
+    }
+
+    #[test]
+    fn unsigned() {
+        let j = vec![0xac, 0x02];
+        assert_eq!(300u64, from_bytes_with_config(&j, config()).unwrap().0);
+
+        let j = vec![0xff, 0xff, 0x03];
+        assert_eq!(u16::MAX, from_bytes_with_config(&j, config()).unwrap().0);
+
+        let j = vec![0xff];
+        assert_eq!(u8::MAX, from_bytes_with_config(&j, config()).unwrap().0);
+    }
+
+    #[test]
+    fn signed() {
+        let j = vec![0x01];
+        assert_eq!(-1i32, from_bytes_with_config(&j, config()).unwrap().0);
+
+        let j = vec![0x02];
+        assert_eq!(1i64, from_bytes_with_config(&j, config()).unwrap().0);
+
+        let j = vec![0xff, 0xff, 0x03];
+        assert_eq!(i16::MIN, from_bytes_with_config(&j, config()).unwrap().0);
+
+        let j = vec![0xfe, 0xff, 0x03];
+        assert_eq!(i16::MAX, from_bytes_with_config(&j, config()).unwrap().0);
+    }
+
+    #[test]
+    fn round_trip() {
+        let config = config();
+        for v in [0i128, 1, -1, 63, -64, 64, -65, i128::MAX, i128::MIN] {
+            let bytes = binja::to_bytes_with_config(&v, config).unwrap();
+            assert_eq!(v, from_bytes_with_config(&bytes, config).unwrap().0);
+        }
+        for v in [0u64, 127, 128, 16383, 16384, u64::MAX] {
+            let bytes = binja::to_bytes_with_config(&v, config).unwrap();
+            assert_eq!(v, from_bytes_with_config(&bytes, config).unwrap().0);
+        }
+    }
+
+    #[test]
+    fn overflow() {
+        let j = vec![0xff, 0xff, 0x04];
+        assert_eq!(
+            Err(Error::InvalidVarint {
+                bytes: vec![0xff, 0xff, 0x04]
+            }),
+            from_bytes_with_config::<u16>(&j, config())
+        );
+    }
+
+    #[test]
+    fn container_lengths_stay_fixed() {
+        let j = vec![0x01, 0x00, 0x00, 0x00, 0x02];
+        assert_eq!(
+            vec![2u32],
+            from_bytes_with_config::<Vec<u32>>(&j, config()).unwrap().0
+        );
+
+        let j = vec![0x01, 0x00, 0x02];
+        assert_eq!(
+            vec![2u32],
+            from_bytes_with_config::<ContainerU16<Vec<u32>>>(&j, config())
+                .unwrap()
+                .0
+                .0
+        );
+    }
+
+    #[test]
+    fn field_overrides() {
+        #[derive(BinaryParse, PartialEq, Debug)]
+        struct Telemetry {
+            #[binja(varint)]
+            counter: u64,
+            #[binja(varint)]
+            delta: i32,
+            raw: u16,
+        }
+
+        let expected = Telemetry {
+            counter: 300,
+            delta: -3,
+            raw: 1,
+        };
+        let j = vec![0xac, 0x02, 0x05, 0x01, 0x00];
+        assert_eq!(expected, from_bytes(&j).unwrap().0);
+
+        #[derive(BinaryParse, PartialEq, Debug)]
+        struct Packet {
+            #[binja(fixed)]
+            id: u32,
+            len: u32,
+        }
+
+        let expected = Packet { id: 1, len: 2 };
+        let j = vec![0x01, 0x00, 0x00, 0x00, 0x02];
+        assert_eq!(expected, from_bytes_with_config(&j, config()).unwrap().0);
+    }
+}
+
+#[cfg(test)]
+mod parser_container_length_overflow {
+    use binja::{config::Config, error::Error, from_bytes_with_config};
+
+    #[test]
+    fn sixteen_bytes() {
+        let config = Config::default().with_container_size_length(16);
+        let mut j = vec![0x00; 16];
+        j[15] = 0x01;
+        assert!(matches!(
+            from_bytes_with_config::<Vec<u8>>(&j, config),
+            Err(Error::Overflow { .. })
+        ));
+    }
+}
+
+#[cfg(test)]
+mod parser_limit {
+    #[cfg(feature = "std")]
+    use std::collections::HashMap;
+
+    #[cfg(feature = "std")]
+    use binja::from_reader_with_config;
+    use binja::{BinaryParser, config::Config, error::Error, from_bytes, from_bytes_with_config};
+
+    #[test]
+    fn integers() {
+        let j = vec![0x01, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00];
+        let config = Config::default().with_limit(6);
+        assert_eq!(
+            Err(Error::LimitExceeded { limit: 6, size: 8 }),
+            from_bytes_with_config::<(u32, u32)>(&j, config)
+        );
+
+        let config = Config::default().with_limit(8);
+        assert_eq!((1, 2), from_bytes_with_config(&j, config).unwrap().0);
+    }
+
+    #[test]
+    fn string() {
+        let mut j = vec![0x10, 0x00, 0x00, 0x00];
+        j.extend([b'a'; 16]);
+        let config = Config::default().with_limit(10);
+        assert_eq!(
+            Err(Error::LimitExceeded {
+                limit: 10,
+                size: 20
+            }),
+            from_bytes_with_config::<String>(&j, config)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn reader() {
+        // a huge string length is refused before anything is read or allocated
+        let j = [0xff, 0xff, 0xff, 0x7f];
+        let config = Config::default().with_limit(1024);
+        assert_eq!(
+            Err(Error::LimitExceeded {
+                limit: 1024,
+                size: 0x7fff_ffff + 4
+            }),
+            from_reader_with_config::<String, _>(&j[..], config)
+        );
+    }
+
+    #[test]
+    fn huge_lengths() {
+        // a 4 byte header can't make the parser allocate gigabytes
+        let j = [0xff, 0xff, 0xff, 0x7f, 0x01];
+        assert!(matches!(
+            from_bytes::<Vec<u64>>(&j).unwrap_err().root_cause(),
+            Error::NoEnoughData { .. }
+        ));
+        #[cfg(feature = "std")]
+        assert!(matches!(
+            from_bytes::<HashMap<u64, u64>>(&j)
+                .unwrap_err()
+                .root_cause(),
+            Error::NoEnoughData { .. }
+        ));
+        #[cfg(feature = "std")]
+        assert!(matches!(
+            from_reader_with_config::<Vec<u64>, _>(&j[..], Config::default())
+                .unwrap_err()
+                .root_cause(),
+            Error::NoEnoughData { .. }
+        ));
+    }
+
+    #[test]
+    fn capacity_hint() {
+        let j = [0u8; 64];
+        let parser = BinaryParser::new(&j, Config::default());
+        assert_eq!(10, parser.capacity_hint::<u64>(10));
+        assert_eq!(64, parser.capacity_hint::<u64>(usize::MAX));
+
+        let parser = BinaryParser::new(&j, Config::default().with_limit(16));
+        assert_eq!(16, parser.capacity_hint::<u8>(usize::MAX));
+    }
+}
+
+#[cfg(test)]
+mod parser_max_depth {
+    use binja::{BinaryParse, config::Config, error::Error, from_bytes_with_config};
+
+    #[derive(BinaryParse, Debug, PartialEq)]
+    struct Node {
+        children: Vec<Node>,
+    }
+
+    // every node has a single child, down to a leaf
+    fn chain(depth: usize) -> Vec<u8> {
+        let mut bytes = [0x01, 0x00, 0x00, 0x00].repeat(depth);
+        bytes.extend([0x00, 0x00, 0x00, 0x00]);
+        bytes
+    }
+
+    #[test]
+    fn recursive_struct() {
+        let config = Config::default().with_max_depth(8);
+        assert!(from_bytes_with_config::<Node>(&chain(3), config).is_ok());
+        assert_eq!(
+            &Error::DepthLimitExceeded { max_depth: 8 },
+            from_bytes_with_config::<Node>(&chain(4), config)
+                .unwrap_err()
+                .root_cause()
+        );
+    }
+
+    #[test]
+    fn hostile_input() {
+        // deep enough to overflow the stack without a depth limit
+        let config = Config::default().with_max_depth(64);
+        assert_eq!(
+            &Error::DepthLimitExceeded { max_depth: 64 },
+            from_bytes_with_config::<Node>(&chain(1_000_000), config)
+                .unwrap_err()
+                .root_cause()
+        );
+    }
+
+    #[test]
+    fn nested_vecs() {
+        let config = Config::default().with_max_depth(2);
+        let j = vec![0x01, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x07];
+        assert_eq!(
+            vec![vec![7u8]],
+            from_bytes_with_config::<Vec<Vec<u8>>>(&j, config)
+                .unwrap()
+                .0
+        );
+
+        let j = vec![
+            0x01, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ];
+        assert_eq!(
+            &Error::DepthLimitExceeded { max_depth: 2 },
+            from_bytes_with_config::<Vec<Vec<Vec<u8>>>>(&j, config)
+                .unwrap_err()
+                .root_cause()
+        );
+    }
+}
+
+#[cfg(test)]
+mod parser_trailing_bytes {
+    use binja::{
+        BinaryParse, config::Config, error::Error, from_bytes, from_bytes_exact,
+        from_bytes_exact_with_config, from_bytes_prefix, from_bytes_prefix_with_config,
+        from_bytes_with_config,
+    };
+
+    #[derive(BinaryParse, PartialEq, Debug)]
+    struct Test {
+        int: u32,
+        seq: Vec<u8>,
+    }
+
+    const BYTES: [u8; 10] = [
+        0x01, 0x00, 0x00, 0x00, // int
+        0x02, 0x00, 0x00, 0x00, // seq size
+        0x03, 0x04, // seq
+    ];
+
+    fn expected() -> Test {
+        Test {
+            int: 1,
+            seq: vec![3, 4],
+        }
+    }
+
+    fn with_trailing() -> Vec<u8> {
+        let mut bytes = BYTES.to_vec();
+        bytes.extend([0xaa, 0xbb, 0xcc]);
+        bytes
+    }
+
+    #[test]
+    fn exact() {
+        assert_eq!(expected(), from_bytes_exact(&BYTES).unwrap());
+        assert_eq!(
+            Err(Error::TrailingBytes {
+                offset: 10,
+                count: 3
+            }),
+            from_bytes_exact::<Test>(&with_trailing())
+        );
+        // the config strategy can't turn the check off
+        let config = Config::default().with_allow_trailing_bytes();
+        assert!(from_bytes_exact_with_config::<Test>(&with_trailing(), config).is_err());
+    }
+
+    #[test]
+    fn config_strategy() {
+        let (value, remaining) = from_bytes::<Test>(&with_trailing()).unwrap();
+        assert_eq!((expected(), 3), (value, remaining));
+
+        let config = Config::default().with_reject_trailing_bytes();
+        assert_eq!(
+            Err(Error::TrailingBytes {
+                offset: 10,
+                count: 3
+            }),
+            from_bytes_with_config::<Test>(&with_trailing(), config)
+        );
+        assert_eq!(
+            (expected(), 0),
+            from_bytes_with_config(&BYTES, config).unwrap()
+        );
+    }
+
+    #[test]
+    fn prefix() {
+        assert_eq!(
+            (expected(), 10),
+            from_bytes_prefix(&with_trailing()).unwrap()
+        );
+
+        // consecutive values
+        let bytes = [0x01, 0x00, 0x02, 0x00, 0x03];
+        let (first, consumed): (u16, usize) = from_bytes_prefix(&bytes).unwrap();
+        let (second, _): (u16, usize) = from_bytes_prefix(&bytes[consumed..]).unwrap();
+        assert_eq!((1, 2, 2), (first, second, consumed));
+
+        // the config strategy can't turn the check on
+        let config = Config::default().with_reject_trailing_bytes();
+        assert_eq!(
+            (expected(), 10),
+            from_bytes_prefix_with_config(&with_trailing(), config).unwrap()
+        );
+    }
+}
+
+#[cfg(test)]
+mod parser_error_context {
+    use binja::{
+        BinaryParse,
+        error::{Error, PathSegment},
+        from_bytes,
+    };
+
+    #[derive(BinaryParse, Debug, PartialEq)]
+    struct Chunk {
+        id: u8,
+        crc: u32,
+    }
+
+    #[derive(BinaryParse, Debug, PartialEq)]
+    enum Payload {
+        Empty,
+        Chunks { chunks: Vec<Chunk> },
+    }
+
+    #[derive(BinaryParse, Debug, PartialEq)]
+    struct Packet {
+        version: u16,
+        payload: Vec<Chunk>,
+    }
+
+    #[derive(BinaryParse, Debug, PartialEq)]
+    struct Wrapper(u8, Payload);
+
+    fn packet_bytes() -> Vec<u8> {
+        let mut bytes = vec![
+            0x01, 0x00, // version
+            0x04, 0x00, 0x00, 0x00, // payload size
+        ];
+        for id in 0..3 {
+            bytes.extend([id, 0xaa, 0xbb, 0xcc, 0xdd]);
+        }
+        // the last chunk is cut in the middle of its crc
+        bytes.extend([3, 0xaa, 0xbb]);
+        bytes
+    }
+
+    #[test]
+    fn struct_path() {
+        let error = from_bytes::<Packet>(&packet_bytes()).unwrap_err();
+        assert_eq!(
+            Error::Context {
+                path: vec![
+                    PathSegment::Type("Packet"),
+                    PathSegment::Field("payload"),
+                    PathSegment::Index(3),
+                    PathSegment::Type("Chunk"),
+                    PathSegment::Field("crc"),
+                ],
+                offset: 0x16,
+                source: Box::new(Error::NoEnoughData {
+                    expected: 4,
+                    available: 2
+                }),
+            },
+            error
+        );
+        assert_eq!(
+            "Packet.payload[3].crc at offset 0x16: Not enough data: expected 4, available 2",
+            error.to_string()
+        );
+        assert_eq!(
+            &Error::NoEnoughData {
+                expected: 4,
+                available: 2
+            },
+            error.root_cause()
+        );
+    }
+
+    #[test]
+    fn enum_path() {
+        let j = vec![
+            0x07, // Wrapper.0
+            0x01, 0x00, 0x00, 0x00, // Payload::Chunks
+            0x01, 0x00, 0x00, 0x00, // chunks size
+            0x01, // id
+        ];
+        let error = from_bytes::<Wrapper>(&j).unwrap_err();
+        assert_eq!(
+            "Wrapper.1::Chunks.chunks[0].crc at offset 0xa: Not enough data: expected 4, available 0",
+            error.to_string()
+        );
+
+        let j = vec![0x07, 0x05, 0x00, 0x00, 0x00];
+        let error = from_bytes::<Wrapper>(&j).unwrap_err();
+        assert!(matches!(error.root_cause(), Error::InvalidVariant { .. }));
+        assert_eq!(
+            "Wrapper.1 at offset 0x5: Invalid variant: expected 0 or 1, found 5",
+            error.to_string()
+        );
+    }
+}
+
+#[cfg(test)]
+mod parser_checkpoint {
+    use binja::{
+        BinaryParse, BinaryParser, config::Config, containers::SizelessContainer, from_bytes,
+        from_bytes_with_config,
+    };
+    #[cfg(feature = "std")]
+    use binja::{IoSource, error::Error, from_reader_with_config};
+
+    // hands out a single byte per read call
+    #[cfg(feature = "std")]
+    struct Trickle<'a>(&'a [u8]);
+
+    #[cfg(feature = "std")]
+    impl std::io::Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            match (self.0.split_first(), buf.first_mut()) {
+                (Some((byte, rest)), Some(slot)) => {
+                    *slot = *byte;
+                    self.0 = rest;
+                    Ok(1)
+                }
+                _ => Ok(0),
+            }
+        }
+    }
+
+    const BYTES: [u8; 7] = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07];
+
+    #[test]
+    fn slice() {
+        let mut parser = BinaryParser::new(&BYTES, Config::default());
+
+        let checkpoint = parser.checkpoint();
+        assert_eq!(0x0201, parser.u16().unwrap());
+        assert_eq!(2, parser.position());
+        parser.restore(checkpoint).unwrap();
+        assert_eq!(0, parser.position());
+
+        assert_eq!(0x01, parser.peek_u8().unwrap());
+        assert_eq!(0x04030201u32, parser.peek().unwrap());
+        assert_eq!(0, parser.position());
+
+        parser.skip(3).unwrap();
+        let checkpoint = parser.checkpoint();
+        assert_eq!(0x04, parser.u8().unwrap());
+        parser.commit(checkpoint);
+        assert_eq!(4, parser.position());
+        assert_eq!(&BYTES[4..], parser.input());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn reader() {
+        let mut parser =
+            BinaryParser::with_source(IoSource::new(Trickle(&BYTES)), Config::default());
+
+        let outer = parser.checkpoint();
+        assert_eq!(0x01, parser.u8().unwrap());
+        let inner = parser.checkpoint();
+        assert_eq!(0x0302, parser.u16().unwrap());
+        parser.restore(inner).unwrap();
+        assert_eq!(0x0302, parser.peek::<u16>().unwrap());
+        assert_eq!(vec![0x02, 0x03, 0x04], parser.bytes(3).unwrap().to_vec());
+        parser.restore(outer).unwrap();
+
+        assert_eq!(0x04030201, parser.u32().unwrap());
+        assert_eq!(0x05, parser.peek_u8().unwrap());
+        parser.skip(1).unwrap();
+        assert_eq!(0x0706, parser.u16().unwrap());
+
+        // a failed read doesn't consume anything either
+        let checkpoint = parser.checkpoint();
+        assert!(parser.u8().is_err());
+        parser.restore(checkpoint).unwrap();
+        assert_eq!(7, parser.position());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn reader_partial_read() {
+        let mut parser =
+            BinaryParser::with_source(IoSource::new(Trickle(&BYTES[..3])), Config::default());
+
+        let checkpoint = parser.checkpoint();
+        assert_eq!(
+            Err(Error::NoEnoughData {
+                expected: 4,
+                available: 3
+            }),
+            parser.u32()
+        );
+        parser.restore(checkpoint).unwrap();
+        assert_eq!(0x0201, parser.u16().unwrap());
+        assert_eq!(0x03, parser.u8().unwrap());
+    }
+
+    #[derive(BinaryParse, PartialEq, Debug)]
+    struct Test {
+        opt: Option<(u8, u32)>,
+        tail: u16,
+    }
+
+    #[test]
+    fn untagged_option() {
+        // the option reads a byte before failing, which must not be lost
+        let config = Config::default().with_untagged_optional();
+        let expected = Test {
+            opt: None,
+            tail: 0x0201,
+        };
+        let j = [0x01, 0x02];
+        assert_eq!(expected, from_bytes_with_config(&j, config).unwrap().0);
+        #[cfg(feature = "std")]
+        assert_eq!(
+            expected,
+            from_reader_with_config(Trickle(&j), config).unwrap()
+        );
+    }
+
+    #[test]
+    fn sizeless_container() {
+        // the last item is incomplete and left unread
+        let j = [0x01, 0x02, 0x00, 0x03];
+        let (value, remaining) = from_bytes::<SizelessContainer<Vec<(u8, u16)>>>(&j).unwrap();
+        assert_eq!(vec![(1, 2)], value.0);
+        assert_eq!(1, remaining);
+    }
+}
+
+#[cfg(test)]
+mod parser_take {
+    use binja::{
+        BinaryParse, BinaryParser, Source, config::Config, error::Error, from_bytes,
+        from_bytes_exact, from_bytes_with_config,
+    };
+    #[cfg(feature = "std")]
+    use binja::{IoSource, from_reader};
+
+    #[test]
+    fn bounded_region() {
+        let j = [0x01, 0x02, 0x03, 0x04, 0x05];
+        let mut parser = BinaryParser::new(&j, Config::default());
+
+        let mut region = parser.take(3);
+        assert_eq!(0x0201, region.u16().unwrap());
+        assert_eq!(
+            Err(Error::NoEnoughData {
+                expected: 2,
+                available: 1
+            }),
+            region.u16()
+        );
+        assert_eq!(0x03, region.u8().unwrap());
+        assert!(region.is_empty());
+        region.finish().unwrap();
+
+        assert_eq!(3, parser.position());
+        assert_eq!(0x04, parser.u8().unwrap());
+    }
+
+    // takes whatever is left in the input
+    #[derive(Debug, PartialEq)]
+    struct Rest(Vec<u8>);
+
+    impl BinaryParse for Rest {
+        fn binary_parse<'de, S: Source<'de>>(
+            parser: &mut BinaryParser<'de, S>,
+        ) -> binja::error::Result<Self> {
+            let rest = parser.input().to_vec();
+            parser.skip(parser.size())?;
+            Ok(Rest(rest))
+        }
+    }
+
+    #[test]
+    fn input_left() {
+        let j = [0x01, 0x02, 0x03, 0x04, 0x05];
+        let (rest, remaining) = from_bytes::<(u8, Rest)>(&j).unwrap();
+        assert_eq!((0x01, Rest(vec![0x02, 0x03, 0x04, 0x05])), rest);
+        assert_eq!(0, remaining);
+
+        let mut parser = BinaryParser::new(&j, Config::default());
+        parser.u8().unwrap();
+        let mut region = parser.take(3);
+        assert_eq!(&j[1..4], region.input());
+        assert_eq!(
+            Rest(vec![0x02, 0x03, 0x04]),
+            Rest::binary_parse(&mut region).unwrap()
+        );
+        region.finish_exact().unwrap();
+        assert_eq!(&j[4..], parser.input());
+        assert_eq!(1, parser.size());
+    }
+
+    #[test]
+    fn finish_skips_or_rejects() {
+        // the trailing bytes strategy only applies to the whole input
+        let j = [0x01, 0x02, 0x03, 0x04];
+        let config = Config::default().with_reject_trailing_bytes();
+        let mut parser = BinaryParser::new(&j, config);
+        let mut region = parser.take(3);
+        assert_eq!(0x01, region.u8().unwrap());
+        region.finish().unwrap();
+        assert_eq!(0x04, parser.u8().unwrap());
+
+        let mut parser = BinaryParser::new(&j, Config::default());
+        let mut region = parser.take(3);
+        assert_eq!(0x01, region.u8().unwrap());
+        assert_eq!(
+            Err(Error::TrailingBytes {
+                offset: 1,
+                count: 2
+            }),
+            region.finish_exact()
+        );
+        let mut region = parser.take(3);
+        region.skip(3).unwrap();
+        region.finish_exact().unwrap();
+    }
+
+    #[test]
+    fn limit_applies() {
+        let j = [0x01, 0x02, 0x03, 0x04];
+        let mut parser = BinaryParser::new(&j, Config::default().with_limit(2));
+        let mut region = parser.take(4);
+        assert_eq!(0x0201, region.u16().unwrap());
+        assert_eq!(Err(Error::LimitExceeded { limit: 2, size: 3 }), region.u8());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn checkpoint_in_region() {
+        let j = [0x01, 0x02, 0x03];
+        let mut parser = BinaryParser::with_source(IoSource::new(&j[..]), Config::default());
+        let mut region = parser.take(2);
+        assert_eq!(0x0201, region.peek::<u16>().unwrap());
+        assert_eq!(0x01, region.u8().unwrap());
+        region.finish().unwrap();
+        assert_eq!(0x03, parser.u8().unwrap());
+    }
+
+    #[derive(BinaryParse, PartialEq, Debug)]
+    struct Inner {
+        a: u8,
+        b: u8,
+    }
+
+    #[derive(BinaryParse, PartialEq, Debug)]
+    struct Packet {
+        len: u16,
+        #[binja(size = "len")]
+        inner: Inner,
+        #[binja(size = "*len - 1")]
+        rest: [u8; 2],
+        tail: u8,
+    }
+
+    #[test]
+    fn size_attribute() {
+        // the region of `inner` has an extra byte it doesn't know about
+        let expected = Packet {
+            len: 3,
+            inner: Inner { a: 1, b: 2 },
+            rest: [0x0a, 0x0b],
+            tail: 0x0c,
+        };
+        let j = [0x03, 0x00, 0x01, 0x02, 0xff, 0x0a, 0x0b, 0x0c];
+        let (value, remaining) = from_bytes::<Packet>(&j).unwrap();
+        assert_eq!(expected, value);
+        assert_eq!(0, remaining);
+        #[cfg(feature = "std")]
+        assert_eq!(expected, from_reader::<Packet, _>(&j[..]).unwrap());
+
+        // unknown bytes in a region aren't trailing bytes of the input
+        assert_eq!(expected, from_bytes_exact::<Packet>(&j).unwrap());
+        let config = Config::default().with_reject_trailing_bytes();
+        assert_eq!(
+            (expected, 0),
+            from_bytes_with_config::<Packet>(&j, config).unwrap()
+        );
+    }
+
+    #[derive(BinaryParse, PartialEq, Debug)]
+    struct ExactPacket {
+        len: u16,
+        #[binja(size = "len", exact)]
+        inner: Inner,
+    }
+
+    #[test]
+    fn size_attribute_exact() {
+        let j = [0x02, 0x00, 0x01, 0x02];
+        let expected = ExactPacket {
+            len: 2,
+            inner: Inner { a: 1, b: 2 },
+        };
+        assert_eq!((expected, 0), from_bytes::<ExactPacket>(&j).unwrap());
+
+        let j = [0x03, 0x00, 0x01, 0x02, 0xff];
+        let error = from_bytes::<ExactPacket>(&j).unwrap_err();
+        assert_eq!(
+            &Error::TrailingBytes {
+                offset: 4,
+                count: 1
+            },
+            error.root_cause()
+        );
+        assert_eq!(
+            "ExactPacket.inner at offset 0x4: Trailing bytes: 1 left at offset 4",
+            error.to_string()
+        );
+    }
+
+    // regions inside regions, as recursive types need
+    #[derive(BinaryParse, PartialEq, Debug)]
+    struct Node {
+        len: u8,
+        #[binja(size = "len")]
+        children: Vec<Node>,
+    }
+
+    #[test]
+    fn nested_regions() {
+        let j = [0x09, 0x01, 0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x00];
+        let leaf = Node {
+            len: 4,
+            children: vec![],
+        };
+        let expected = Node {
+            len: 9,
+            children: vec![leaf],
+        };
+        assert_eq!((expected, 0), from_bytes::<Node>(&j).unwrap());
+    }
+}
+
+#[cfg(test)]
+mod parser_count {
+    use binja::{BinaryParse, error::Error, from_bytes};
+
+    #[derive(BinaryParse, PartialEq, Debug)]
+    struct Legacy {
+        num_items: u8,
+        flags: u8,
+        #[binja(count = "num_items")]
+        items: Vec<u16>,
+        #[binja(count = "*flags & 0x0f")]
+        extra: Vec<u8>,
+    }
+
+    #[test]
+    fn count_from_fields() {
+        let j = [0x02, 0xf1, 0x01, 0x00, 0x02, 0x00, 0x09, 0xff];
+        let expected = Legacy {
+            num_items: 2,
+            flags: 0xf1,
+            items: vec![1, 2],
+            extra: vec![9],
+        };
+        assert_eq!((expected, 1), from_bytes::<Legacy>(&j).unwrap());
+    }
+
+    #[test]
+    fn count_not_available() {
+        let j = [0x03, 0x00, 0x01, 0x00, 0x02, 0x00];
+        let error = from_bytes::<Legacy>(&j).unwrap_err();
+        assert_eq!(
+            &Error::NoEnoughData {
+                expected: 2,
+                available: 0
+            },
+            error.root_cause()
+        );
+        assert_eq!(
+            "Legacy.items[2] at offset 0x6: Not enough data: expected 2, available 0",
+            error.to_string()
+        );
+    }
+}
+
+#[cfg(test)]
+mod parser_conditional {
+    use binja::{BinaryParse, from_bytes};
+
+    #[derive(BinaryParse, PartialEq, Debug)]
+    struct Frame {
+        flags: u8,
+        #[binja(if = "flags & 0x4 != 0")]
+        timestamp: Option<u32>,
+        #[binja(if = "flags & 0x1 != 0", varint)]
+        sequence: Option<u16>,
+        payload: u8,
+    }
+
+    #[test]
+    fn present() {
+        let j = [0x05, 0x01, 0x00, 0x00, 0x00, 0xac, 0x02, 0xff];
+        let expected = Frame {
+            flags: 0x05,
+            timestamp: Some(1),
+            sequence: Some(300),
+            payload: 0xff,
+        };
+        assert_eq!((expected, 0), from_bytes::<Frame>(&j).unwrap());
+    }
+
+    #[test]
+    fn absent() {
+        // no tag byte is read for absent fields
+        let j = [0x04, 0x01, 0x00, 0x00, 0x00, 0xff];
+        let expected = Frame {
+            flags: 0x04,
+            timestamp: Some(1),
+            sequence: None,
+            payload: 0xff,
+        };
+        assert_eq!((expected, 0), from_bytes::<Frame>(&j).unwrap());
+
+        let j = [0x00, 0xff];
+        let expected = Frame {
+            flags: 0x00,
+            timestamp: None,
+            sequence: None,
+            payload: 0xff,
+        };
+        assert_eq!((expected, 0), from_bytes::<Frame>(&j).unwrap());
+    }
+
+    #[test]
+    fn error_path() {
+        let j = [0x04, 0x01, 0x00];
+        assert_eq!(
+            "Frame.timestamp at offset 0x1: Not enough data: expected 4, available 2",
+            from_bytes::<Frame>(&j).unwrap_err().to_string()
+        );
+    }
+}
+
+#[cfg(test)]
+mod parser_magic {
+    use binja::{
+        BinaryParse, BinaryParser, BinarySerialize, config::Config, error::Error, from_bytes,
+    };
+
+    #[derive(BinaryParse, PartialEq, Debug)]
+    #[binja(magic = b"PK\x03\x04")]
+    struct Header {
+        #[binja(const = 0x0014)]
+        version: u16,
+        flags: u8,
+    }
+
+    #[test]
+    fn magic_and_const() {
+        let j = [b'P', b'K', 0x03, 0x04, 0x14, 0x00, 0x01];
+        let expected = Header {
+            version: 0x14,
+            flags: 1,
+        };
+        assert_eq!((expected, 0), from_bytes::<Header>(&j).unwrap());
+    }
+
+    #[test]
+    fn magic_mismatch() {
+        let j = [b'P', b'K', 0x05, 0x06, 0x14, 0x00, 0x01];
+        let error = from_bytes::<Header>(&j).unwrap_err();
+        assert_eq!(
+            &Error::MagicMismatch {
+                expected: b"PK\x03\x04".to_vec(),
+                found: b"PK\x05\x06".to_vec()
+            },
+            error.root_cause()
+        );
+        assert_eq!(
+            "Header at offset 0x0: Magic mismatch: expected [50, 4b, 03, 04], found [50, 4b, 05, 06]",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn const_mismatch() {
+        let j = [b'P', b'K', 0x03, 0x04, 0x15, 0x00, 0x01];
+        assert_eq!(
+            "Header.version at offset 0x4: Magic mismatch: expected [14, 00], found [15, 00]",
+            from_bytes::<Header>(&j).unwrap_err().to_string()
+        );
+    }
+
+    #[derive(BinaryParse, BinarySerialize, PartialEq, Debug)]
+    struct Marker {
+        tag: u8,
+        #[binja(align = 4)]
+        value: u16,
+    }
+
+    #[derive(BinaryParse, PartialEq, Debug)]
+    struct Record {
+        kind: u8,
+        #[binja(const = Marker { tag: 1, value: 2 })]
+        marker: Marker,
+    }
+
+    #[test]
+    fn const_aligned_at_position() {
+        let j = [0x07, 0x01, 0x00, 0x00, 0x02, 0x00];
+        let expected = Record {
+            kind: 7,
+            marker: Marker { tag: 1, value: 2 },
+        };
+        assert_eq!((expected, 0), from_bytes::<Record>(&j).unwrap());
+    }
+
+    #[test]
+    fn const_mismatch_inside_value() {
+        let j = [0x07, 0x01, 0x00, 0x00, 0x03, 0x00];
+        assert_eq!(
+            "Record.marker at offset 0x1: Magic mismatch: expected [02, 00], found [03, 00]",
+            from_bytes::<Record>(&j).unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn const_follows_config() {
+        let j = [b'P', b'K', 0x03, 0x04, 0x00, 0x14, 0x01];
+        let mut parser = BinaryParser::new(&j, Config::default().with_big_endian());
+        assert_eq!(
+            Header {
+                version: 0x14,
+                flags: 1
+            },
+            Header::binary_parse(&mut parser).unwrap()
+        );
+    }
+}
+
+#[cfg(test)]
+mod parser_padding {
+    use binja::{BinaryParse, error::Error, from_bytes};
+
+    #[derive(BinaryParse, PartialEq, Debug)]
+    #[binja(pad_to = 12)]
+    struct Registers {
+        control: u8,
+        #[binja(align = 4)]
+        status: u32,
+        #[binja(pad_before = 1, pad_after = 1)]
+        mode: u8,
+    }
+
+    #[test]
+    fn skipped() {
+        let j = [
+            0x01, 0xaa, 0xaa, 0xaa, 0x02, 0x00, 0x00, 0x00, 0xaa, 0x03, 0xaa, 0xaa, 0x04,
+        ];
+        let expected = Registers {
+            control: 1,
+            status: 2,
+            mode: 3,
+        };
+        assert_eq!((expected, 1), from_bytes::<Registers>(&j).unwrap());
+    }
+
+    #[derive(BinaryParse, PartialEq, Debug)]
+    #[binja(pad_to = 4, fill = 0xff, check_padding)]
+    struct Checked {
+        #[binja(pad_after = 1)]
+        a: u8,
+        b: u8,
+    }
+
+    #[test]
+    fn checked() {
+        let j = [0x01, 0xff, 0x02, 0xff];
+        let expected = Checked { a: 1, b: 2 };
+        assert_eq!((expected, 0), from_bytes::<Checked>(&j).unwrap());
+
+        let j = [0x01, 0x00, 0x02, 0xff];
+        let error = from_bytes::<Checked>(&j).unwrap_err();
+        assert_eq!(
+            &Error::InvalidPadding {
+                expected: 0xff,
+                found: vec![0x00]
+            },
+            error.root_cause()
+        );
+        assert_eq!(
+            "Checked.a at offset 0x2: Invalid padding: expected 0xff bytes, found [00]",
+            error.to_string()
+        );
+
+        let j = [0x01, 0xff, 0x02, 0x00];
+        assert_eq!(
+            "Checked at offset 0x4: Invalid padding: expected 0xff bytes, found [00]",
+            from_bytes::<Checked>(&j).unwrap_err().to_string()
+        );
+    }
+
+    #[derive(BinaryParse, PartialEq, Debug)]
+    struct Nested {
+        tag: u8,
+        #[binja(align = 2)]
+        inner: Checked,
+    }
+
+    #[test]
+    fn absolute_position() {
+        let j = [0x09, 0x00, 0x01, 0xff, 0x02, 0xff];
+        let expected = Nested {
+            tag: 9,
+            inner: Checked { a: 1, b: 2 },
+        };
+        assert_eq!((expected, 0), from_bytes::<Nested>(&j).unwrap());
+    }
+}
+
+#[cfg(test)]
+mod parser_endianness_override {
+    use binja::{BinaryParse, BinaryParser, config::Config, from_bytes};
+
+    #[derive(BinaryParse, PartialEq, Debug)]
+    #[binja(big_endian)]
+    struct NetworkHeader {
+        port: u16,
+        #[binja(little_endian)]
+        local: u16,
+    }
+
+    #[derive(BinaryParse, PartialEq, Debug)]
+    struct Record {
+        len: u16,
+        header: NetworkHeader,
+        #[binja(big_endian)]
+        id: u32,
+        crc: u16,
+    }
+
+    #[test]
+    fn mixed() {
+        let j = [
+            0x01, 0x00, 0x00, 0x50, 0x02, 0x00, 0x00, 0x00, 0x00, 0x03, 0x04, 0x00,
+        ];
+        let expected = Record {
+            len: 1,
+            header: NetworkHeader { port: 80, local: 2 },
+            id: 3,
+            crc: 4,
+        };
+        assert_eq!((expected, 0), from_bytes::<Record>(&j).unwrap());
+    }
+
+    #[derive(BinaryParse, PartialEq, Debug)]
+    #[binja(little_endian, repr = "u16")]
+    enum Message {
+        Ping,
+        Data(u32),
+    }
+
+    #[test]
+    fn enum_override() {
+        // the tag follows the override as well
+        let j = [0x01, 0x00, 0x05, 0x00, 0x00, 0x00];
+        let mut parser = BinaryParser::new(&j, Config::default().with_big_endian());
+        assert_eq!(
+            Message::Data(5),
+            Message::binary_parse(&mut parser).unwrap()
+        );
+
+        // and the configuration is restored afterwards
+        let j = [0x00, 0x00, 0x00, 0x01];
+        let mut parser = BinaryParser::new(&j, Config::default().with_big_endian());
+        assert_eq!(Message::Ping, Message::binary_parse(&mut parser).unwrap());
+        assert_eq!(1, parser.u16().unwrap());
+    }
+}
+
+#[cfg(test)]
+mod parser_custom_codec {
+    use binja::{BinaryParse, BinaryParser, Source, error::Result, from_bytes};
+    use core::time::Duration;
+
+    mod seconds {
+        use binja::{BinaryParser, Source, error::Result};
+        use core::time::Duration;
+
+        pub fn binary_parse<'de, S: Source<'de>>(
+            parser: &mut BinaryParser<'de, S>,
+        ) -> Result<Duration> {
+            Ok(Duration::from_secs(parser.u32()?.into()))
+        }
+    }
+
+    fn from_byte<'de, S: Source<'de>>(parser: &mut BinaryParser<'de, S>) -> Result<bool> {
+        Ok(parser.u8()? != 0)
+    }
+
+    #[derive(BinaryParse, PartialEq, Debug)]
+    struct Entry {
+        #[binja(with = "seconds")]
+        timeout: Duration,
+        #[binja(parse_with = "from_byte")]
+        enabled: bool,
+        #[binja(parse_with = "from_byte")]
+        other: bool,
+    }
+
+    #[test]
+    fn fields() {
+        let j = [0x03, 0x00, 0x00, 0x00, 0xff, 0x00];
+        let expected = Entry {
+            timeout: Duration::from_secs(3),
+            enabled: true,
+            other: false,
+        };
+        assert_eq!((expected, 0), from_bytes::<Entry>(&j).unwrap());
+    }
+
+    #[derive(BinaryParse, PartialEq, Debug)]
+    enum Command {
+        Wait(#[binja(with = "seconds")] Duration),
+        Toggle {
+            #[binja(parse_with = "from_byte")]
+            on: bool,
+        },
+    }
+
+    #[test]
+    fn enum_variant_fields() {
+        let j = [0x00, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00];
+        assert_eq!(
+            (Command::Wait(Duration::from_secs(2)), 0),
+            from_bytes::<Command>(&j).unwrap()
+        );
+        let j = [0x01, 0x00, 0x00, 0x00, 0x07];
+        assert_eq!(
+            (Command::Toggle { on: true }, 0),
+            from_bytes::<Command>(&j).unwrap()
+        );
+    }
+}
+
+#[cfg(test)]
+mod parser_map_and_assert {
+    use binja::{BinaryParse, error::Error, from_bytes};
+
+    #[derive(PartialEq, Debug)]
+    enum Kind {
+        File,
+        Directory,
+    }
+
+    impl TryFrom<u8> for Kind {
+        type Error = &'static str;
+
+        fn try_from(value: u8) -> Result<Self, Self::Error> {
+            match value {
+                0 => Ok(Kind::File),
+                1 => Ok(Kind::Directory),
+                _ => Err("unknown kind"),
+            }
+        }
+    }
+
+    #[derive(BinaryParse, PartialEq, Debug)]
+    struct Entry {
+        #[binja(assert = "*version < 3", message = "unsupported version")]
+        version: u8,
+        #[binja(try_map = "|raw: u8| Kind::try_from(raw)")]
+        kind: Kind,
+        #[binja(map = "|raw: u16| u32::from(raw) * 512")]
+        size: u32,
+        #[binja(assert = "*blocks * 512 >= *size")]
+        blocks: u32,
+    }
+
+    #[test]
+    fn valid() {
+        let j = [0x02, 0x01, 0x02, 0x00, 0x02, 0x00, 0x00, 0x00];
+        let expected = Entry {
+            version: 2,
+            kind: Kind::Directory,
+            size: 1024,
+            blocks: 2,
+        };
+        assert_eq!((expected, 0), from_bytes::<Entry>(&j).unwrap());
+    }
+
+    #[test]
+    fn assert_message() {
+        let j = [0x03, 0x01, 0x02, 0x00, 0x02, 0x00, 0x00, 0x00];
+        assert_eq!(
+            &Error::Validation {
+                field: "version".to_owned(),
+                message: "unsupported version".to_owned()
+            },
+            from_bytes::<Entry>(&j).unwrap_err().root_cause()
+        );
+    }
+
+    #[test]
+    fn assert_default_message() {
+        let j = [0x02, 0x01, 0x02, 0x00, 0x01, 0x00, 0x00, 0x00];
+        let Error::Validation { field, message } =
+            from_bytes::<Entry>(&j).unwrap_err().root_cause().clone()
+        else {
+            panic!("expected a validation error");
+        };
+        assert_eq!("blocks", field);
+        assert_eq!("assertion failed: *blocks * 512 >= *size", message);
+    }
+
+    #[test]
+    fn try_map_error() {
+        let j = [0x02, 0x05, 0x02, 0x00, 0x02, 0x00, 0x00, 0x00];
+        let error = from_bytes::<Entry>(&j).unwrap_err();
+        assert_eq!(
+            &Error::Validation {
+                field: "kind".to_owned(),
+                message: "unknown kind".to_owned()
+            },
+            error.root_cause()
+        );
+        assert_eq!(
+            "Entry.kind at offset 0x2: Validation failed for `kind`: unknown kind",
+            error.to_string()
+        );
+    }
+
+    // `into` gives the wire type, so plain functions can be used
+    #[derive(BinaryParse, PartialEq, Debug)]
+    struct Node {
+        #[binja(try_map = "Kind::try_from", into = "u8")]
+        kind: Kind,
+        #[binja(map = "u32::from", into = "u16")]
+        size: u32,
+    }
+
+    #[test]
+    fn path_functions() {
+        let j = [0x00, 0x02, 0x00];
+        assert_eq!(
+            (
+                Node {
+                    kind: Kind::File,
+                    size: 2
+                },
+                0
+            ),
+            from_bytes::<Node>(&j).unwrap()
+        );
+        let j = [0x02, 0x02, 0x00];
+        assert_eq!(
+            &Error::Validation {
+                field: "kind".to_owned(),
+                message: "unknown kind".to_owned()
+            },
+            from_bytes::<Node>(&j).unwrap_err().root_cause()
+        );
+    }
+
+    // `if` is only an attribute key in key position, not inside a value
+    #[derive(BinaryParse, PartialEq, Debug)]
+    struct Versioned {
+        version: u8,
+        #[binja(assert = if *version > 1 { *flags != 0 } else { true }, message = "no flags")]
+        flags: u8,
+    }
+
+    #[test]
+    fn keyword_in_value() {
+        let j = [0x01, 0x00];
+        let expected = Versioned {
+            version: 1,
+            flags: 0,
+        };
+        assert_eq!((expected, 0), from_bytes::<Versioned>(&j).unwrap());
+        let j = [0x02, 0x00];
+        assert_eq!(
+            &Error::Validation {
+                field: "flags".to_owned(),
+                message: "no flags".to_owned()
+            },
+            from_bytes::<Versioned>(&j).unwrap_err().root_cause()
+        );
+    }
+
+    #[derive(BinaryParse, PartialEq, Debug)]
+    enum Command {
+        Seek {
+            #[binja(assert = "*offset % 512 == 0")]
+            offset: u32,
+        },
+        Open(#[binja(try_map = "|raw: u8| Kind::try_from(raw)", if = "true")] Option<Kind>),
+    }
+
+    #[test]
+    fn enum_variant_fields() {
+        let j = [0x00, 0x00, 0x00, 0x00, 0x00, 0x04, 0x00, 0x00];
+        assert_eq!(
+            (Command::Seek { offset: 1024 }, 0),
+            from_bytes::<Command>(&j).unwrap()
+        );
+        let j = [0x00, 0x00, 0x00, 0x00, 0x01, 0x04, 0x00, 0x00];
+        assert_eq!(
+            &Error::Validation {
+                field: "offset".to_owned(),
+                message: "assertion failed: *offset % 512 == 0".to_owned()
+            },
+            from_bytes::<Command>(&j).unwrap_err().root_cause()
+        );
+        let j = [0x01, 0x00, 0x00, 0x00, 0x00];
+        assert_eq!(
+            (Command::Open(Some(Kind::File)), 0),
+            from_bytes::<Command>(&j).unwrap()
+        );
+    }
+}
+
+#[cfg(test)]
+mod parser_default_and_calc {
+    use binja::{BinaryParse, from_bytes};
+
+    #[derive(PartialEq, Debug)]
+    enum Origin {
+        Wire,
+        Local,
+    }
+
+    #[derive(BinaryParse, PartialEq, Debug)]
+    struct Image {
+        width: u16,
+        height: u16,
+        #[binja(calc = "u32::from(*width) * u32::from(*height)")]
+        pixels: u32,
+        #[binja(skip, default = "Origin::Wire")]
+        origin: Origin,
+        depth: u8,
+    }
+
+    #[test]
+    fn fields() {
+        let j = [0x02, 0x00, 0x03, 0x00, 0x08];
+        let expected = Image {
+            width: 2,
+            height: 3,
+            pixels: 6,
+            origin: Origin::Wire,
+            depth: 8,
+        };
+        assert_eq!((expected, 0), from_bytes::<Image>(&j).unwrap());
+    }
+
+    #[derive(BinaryParse, PartialEq, Debug)]
+    enum Shape {
+        Square {
+            side: u8,
+            #[binja(calc = "*side * *side")]
+            area: u8,
+        },
+        Unknown(#[binja(skip, default = "Origin::Local")] Origin),
+    }
+
+    #[test]
+    fn enum_variant_fields() {
+        let j = [0x00, 0x00, 0x00, 0x00, 0x03];
+        assert_eq!(
+            (Shape::Square { side: 3, area: 9 }, 0),
+            from_bytes::<Shape>(&j).unwrap()
+        );
+        let j = [0x01, 0x00, 0x00, 0x00];
+        assert_eq!(
+            (Shape::Unknown(Origin::Local), 0),
+            from_bytes::<Shape>(&j).unwrap()
+        );
+    }
+}
+
+#[cfg(test)]
+mod parser_variant_tags {
+    use binja::{BinaryParse, error::Error, from_bytes};
+
+    mod opcodes {
+        pub const PUSH: u8 = 0x20;
+        pub const PNG: [u8; 4] = *b"\x89PNG";
+    }
+
+    #[derive(BinaryParse, PartialEq, Debug)]
+    #[binja(repr = "u8")]
+    enum Instruction {
+        #[binja(tag = 0x10)]
+        Nop,
+        // continues from the discriminant, not the tag
+        Halt,
+        #[binja(tag = opcodes::PUSH)]
+        Push(u16),
+        #[binja(tag = 0xff)]
+        Unsupported,
+    }
+
+    #[derive(BinaryParse, PartialEq, Debug)]
+    enum Image {
+        #[binja(tag = opcodes::PNG)]
+        Png { width: u16 },
+        #[binja(tag = b"GIF8")]
+        Gif(u8),
+    }
+
+    #[test]
+    fn integer_tags() {
+        assert_eq!(
+            (Instruction::Nop, 0),
+            from_bytes::<Instruction>(&[0x10]).unwrap()
+        );
+        assert_eq!(
+            (Instruction::Halt, 0),
+            from_bytes::<Instruction>(&[0x01]).unwrap()
+        );
+        assert_eq!(
+            (Instruction::Push(7), 0),
+            from_bytes::<Instruction>(&[0x20, 0x07, 0x00]).unwrap()
+        );
+        assert_eq!(
+            &Error::InvalidVariant {
+                expected: "16, 1, opcodes::PUSH or 255".to_owned(),
+                found: "2".to_owned()
+            },
+            from_bytes::<Instruction>(&[0x02]).unwrap_err().root_cause()
+        );
+    }
+
+    #[test]
+    fn byte_string_tags() {
+        assert_eq!(
+            (Image::Png { width: 5 }, 0),
+            from_bytes::<Image>(b"\x89PNG\x05\x00").unwrap()
+        );
+        assert_eq!(
+            (Image::Gif(3), 0),
+            from_bytes::<Image>(b"GIF8\x03").unwrap()
+        );
+        assert_eq!(
+            &Error::InvalidVariant {
+                expected: "opcodes::PNG or [47, 49, 46, 38]".to_owned(),
+                found: "[42, 4d, 50, 20]".to_owned()
+            },
+            from_bytes::<Image>(b"BMP \x00").unwrap_err().root_cause()
+        );
+    }
+}
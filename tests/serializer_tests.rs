@@ -146,6 +146,7 @@ mod serializer_little_endian_tagged_optional {
     }
 
     #[test]
+    #[cfg(feature = "std")]
     fn map() {
         use std::collections::HashMap;
 
@@ -416,6 +417,7 @@ mod serializer_big_endian_untagged_optional {
     }
 
     #[test]
+    #[cfg(feature = "std")]
     fn map() {
         use std::collections::HashMap;
 
@@ -515,3 +517,1199 @@ mod serializer_big_endian_untagged_optional {
         assert_eq!(expected, to_bytes(&j).unwrap());
     }
 }
+
+#[cfg(all(test, feature = "std"))]
+mod serializer_writer {
+    use binja::{
+        BinarySerialize, config::Config, error::Error, to_bytes, to_writer, to_writer_with_config,
+    };
+
+    #[derive(BinarySerialize)]
+    struct Test {
+        int: u32,
+        opt: Option<u32>,
+        seq: Vec<String>,
+    }
+
+    fn value() -> Test {
+        Test {
+            int: 1,
+            opt: Some(2),
+            seq: vec!["a".to_owned(), "b".to_owned()],
+        }
+    }
+
+    #[test]
+    fn same_output_as_bytes() {
+        let mut output = Vec::new();
+        to_writer(&value(), &mut output).unwrap();
+        assert_eq!(to_bytes(&value()).unwrap(), output);
+    }
+
+    #[test]
+    fn limit() {
+        let mut output = Vec::new();
+        let config = Config::default().with_limit(8);
+        assert_eq!(
+            Err(Error::LimitExceeded { limit: 8, size: 9 }),
+            to_writer_with_config(&value(), &mut output, config)
+        );
+        // nothing past the limit reaches the writer
+        assert_eq!(5, output.len());
+    }
+
+    #[test]
+    fn io_error() {
+        let mut buffer = [0u8; 4];
+        let result = to_writer(&value(), &mut buffer[..]);
+        assert!(matches!(
+            result,
+            Err(Error::Io {
+                kind: std::io::ErrorKind::WriteZero,
+                ..
+            })
+        ));
+    }
+}
+
+#[cfg(test)]
+mod serializer_slice {
+    use binja::{BinarySerialize, error::Error, to_bytes, to_slice};
+
+    #[derive(BinarySerialize)]
+    struct Test {
+        int: u32,
+        opt: Option<u32>,
+        seq: Vec<String>,
+    }
+
+    fn value() -> Test {
+        Test {
+            int: 1,
+            opt: Some(2),
+            seq: vec!["a".to_owned(), "b".to_owned()],
+        }
+    }
+
+    #[test]
+    fn same_output_as_bytes() {
+        let mut buf = [0xffu8; 32];
+        let size = to_slice(&value(), &mut buf).unwrap();
+        assert_eq!(23, size);
+        assert_eq!(to_bytes(&value()).unwrap(), buf[..size]);
+        // the rest of the buffer is left untouched
+        assert!(buf[size..].iter().all(|b| *b == 0xff));
+    }
+
+    #[test]
+    fn buffer_full() {
+        let mut buf = [0u8; 8];
+        assert_eq!(
+            Err(Error::BufferFull {
+                capacity: 8,
+                required: 9
+            }),
+            to_slice(&value(), &mut buf)
+        );
+    }
+}
+
+#[cfg(test)]
+mod serializer_size {
+    use binja::{
+        BinarySerialize,
+        config::{Config, OptionalStrategy},
+        containers::ContainerU8,
+        error::Error,
+        serialized_size, to_bytes_with_config,
+    };
+
+    #[derive(BinarySerialize)]
+    struct Test {
+        #[binja(bits = 3)]
+        flags: u8,
+        #[binja(bits = 7)]
+        level: u8,
+        opt: Option<u32>,
+        seq: Vec<String>,
+        short: ContainerU8<Vec<u16>>,
+        #[binja(skip)]
+        skipped: u64,
+    }
+
+    fn value() -> Test {
+        Test {
+            flags: 5,
+            level: 100,
+            opt: None,
+            seq: vec!["a".to_owned(), "bc".to_owned()],
+            short: ContainerU8::new(vec![1, 2, 3]),
+            skipped: 9,
+        }
+    }
+
+    #[test]
+    fn matches_serialized_length() {
+        let configs = [
+            Config::default(),
+            Config::default().with_untagged_optional(),
+            Config::default().with_container_size_length(1),
+            Config::default()
+                .with_container_size_length(16)
+                .with_big_endian(),
+        ];
+
+        for config in configs {
+            let size = serialized_size(&value(), config).unwrap();
+            assert_eq!(to_bytes_with_config(&value(), config).unwrap().len(), size);
+        }
+    }
+
+    #[test]
+    fn exact_size() {
+        // 2 bit field bytes, 1 tag, 4 + (4 + 1) + (4 + 2) seq, 1 + 6 short
+        assert_eq!(25, serialized_size(&value(), Config::default()).unwrap());
+
+        let config = Config {
+            optional_strategy: OptionalStrategy::Untagged,
+            ..Default::default()
+        };
+        assert_eq!(24, serialized_size(&value(), config).unwrap());
+    }
+
+    #[test]
+    fn limit() {
+        let config = Config::default().with_limit(10);
+        assert!(matches!(
+            serialized_size(&value(), config),
+            Err(Error::LimitExceeded { limit: 10, .. })
+        ));
+    }
+}
+
+#[cfg(test)]
+mod fixed_encoded_size {
+    use binja::{BinarySerialize, FixedEncodedSize, config::Config, serialized_size};
+
+    #[derive(BinarySerialize, Default)]
+    struct Flags {
+        #[binja(bits = 1)]
+        power: u8,
+        #[binja(bits = 2)]
+        mode: u8,
+        #[binja(skip)]
+        cached: Vec<u8>,
+        #[binja(bits = 6)]
+        error_code: u8,
+    }
+
+    #[derive(BinarySerialize, Default)]
+    struct Header {
+        flags: Flags,
+        length: u16,
+        checksum: [u8; 4],
+        pair: (u32, i8),
+        #[binja(bits = 4)]
+        version: u8,
+    }
+
+    #[derive(BinarySerialize, Default)]
+    struct Position(f32, f32, f64);
+
+    const _: () = assert!(Header::SIZE == 14);
+
+    #[test]
+    fn primitives() {
+        assert_eq!(1, bool::SIZE);
+        assert_eq!(16, i128::SIZE);
+        assert_eq!(8, f64::SIZE);
+        assert_eq!(12, <[u16; 6]>::SIZE);
+        assert_eq!(7, <(u8, u16, f32)>::SIZE);
+        assert_eq!(0, <()>::SIZE);
+    }
+
+    #[test]
+    fn derived() {
+        let buffer = [0u8; Header::SIZE];
+        assert_eq!(14, buffer.len());
+        assert_eq!(2, Flags::SIZE);
+        assert_eq!(16, Position::SIZE);
+
+        let config = Config::default();
+        assert_eq!(
+            Header::SIZE,
+            serialized_size(&Header::default(), config).unwrap()
+        );
+        assert_eq!(
+            Position::SIZE,
+            serialized_size(&Position::default(), config).unwrap()
+        );
+    }
+}
+
+#[cfg(test)]
+mod serializer_varint_container_size {
+    use binja::{config::Config, to_bytes_with_config};
+
+    #[test]
+    fn string() {
+        let config = Config::default().with_varint_container_size();
+        assert_eq!(
+            vec![0x01, b'a'],
+            to_bytes_with_config(&"a", config).unwrap()
+        );
+        assert_eq!(vec![0x00], to_bytes_with_config(&"", config).unwrap());
+    }
+
+    #[test]
+    fn seq() {
+        let config = Config::default().with_varint_container_size();
+
+        let bytes = to_bytes_with_config(&vec![0u8; 127], config).unwrap();
+        assert_eq!([0x7f], bytes[..1]);
+        assert_eq!(128, bytes.len());
+
+        let bytes = to_bytes_with_config(&vec![0u8; 300], config).unwrap();
+        assert_eq!([0xac, 0x02], bytes[..2]);
+        assert_eq!(302, bytes.len());
+    }
+}
+
+#[cfg(test)]
+mod serializer_varint_encoding {
+    use binja::{
+        BinarySerialize, config::Config, containers::ContainerU16, to_bytes, to_bytes_with_config,
+    };
+
+    fn config() -> Config {
+        Config::default().with_varint_encoding()
+    }
+
+    #[test]
+    fn unsigned() {
+        assert_eq!(vec![0x01], to_bytes_with_config(&1u16, config()).unwrap());
+        assert_eq!(vec![0x7f], to_bytes_with_config(&127u32, config()).unwrap());
+        assert_eq!(
+            vec![0xac, 0x02],
+            to_bytes_with_config(&300u64, config()).unwrap()
+        );
+        assert_eq!(
+            vec![0xff, 0xff, 0x03],
+            to_bytes_with_config(&u16::MAX, config()).unwrap()
+        );
+        assert_eq!(vec![0x00], to_bytes_with_config(&0u128, config()).unwrap());
+        // single bytes keep their width
+        assert_eq!(
+            vec![0xff],
+            to_bytes_with_config(&u8::MAX, config()).unwrap()
+        );
+    }
+
+    #[test]
+    fn signed() {
+        assert_eq!(vec![0x00], to_bytes_with_config(&0i16, config()).unwrap());
+        assert_eq!(vec![0x01], to_bytes_with_config(&-1i32, config()).unwrap());
+        assert_eq!(vec![0x02], to_bytes_with_config(&1i64, config()).unwrap());
+        assert_eq!(vec![0x03], to_bytes_with_config(&-2i128, config()).unwrap());
+        assert_eq!(
+            vec![0xff, 0xff, 0x03],
+            to_bytes_with_config(&i16::MIN, config()).unwrap()
+        );
+        assert_eq!(vec![0xff], to_bytes_with_config(&-1i8, config()).unwrap());
+    }
+
+    #[test]
+    fn container_lengths_stay_fixed() {
+        assert_eq!(
+            vec![0x01, 0x00, 0x00, 0x00, 0x02],
+            to_bytes_with_config(&vec![2u32], config()).unwrap()
+        );
+        assert_eq!(
+            vec![0x01, 0x00, 0x02],
+            to_bytes_with_config(&ContainerU16::new(vec![2u32]), config()).unwrap()
+        );
+    }
+
+    #[test]
+    fn field_overrides() {
+        #[derive(BinarySerialize)]
+        struct Telemetry {
+            #[binja(varint)]
+            counter: u64,
+            #[binja(varint)]
+            delta: i32,
+            raw: u16,
+        }
+
+        let value = Telemetry {
+            counter: 300,
+            delta: -3,
+            raw: 1,
+        };
+        assert_eq!(
+            vec![0xac, 0x02, 0x05, 0x01, 0x00],
+            to_bytes(&value).unwrap()
+        );
+
+        #[derive(BinarySerialize)]
+        struct Packet {
+            #[binja(fixed)]
+            id: u32,
+            len: u32,
+        }
+
+        let value = Packet { id: 1, len: 2 };
+        assert_eq!(
+            vec![0x01, 0x00, 0x00, 0x00, 0x02],
+            to_bytes_with_config(&value, config()).unwrap()
+        );
+    }
+}
+
+#[cfg(test)]
+mod serializer_container_length_overflow {
+    use binja::{
+        config::Config,
+        containers::{ContainerU8, ContainerU16},
+        error::Error,
+        to_bytes_with_config,
+    };
+
+    #[test]
+    fn seq() {
+        let config = Config::default().with_container_size_length(1);
+        assert_eq!(
+            Err(Error::Overflow {
+                value: "300".to_owned(),
+                max: "255".to_owned(),
+            }),
+            to_bytes_with_config(&vec![0u8; 300], config)
+        );
+        assert_eq!(
+            256,
+            to_bytes_with_config(&vec![0u8; 255], config).unwrap().len()
+        );
+    }
+
+    #[test]
+    fn string() {
+        let config = Config::default().with_container_size_length(1);
+        assert!(matches!(
+            to_bytes_with_config(&"a".repeat(256), config),
+            Err(Error::Overflow { .. })
+        ));
+    }
+
+    #[test]
+    fn fixed_size_container() {
+        let config = Config::default();
+        assert_eq!(
+            Err(Error::Overflow {
+                value: "256".to_owned(),
+                max: "255".to_owned(),
+            }),
+            to_bytes_with_config(&ContainerU8::new(vec![0u8; 256]), config)
+        );
+        assert!(to_bytes_with_config(&ContainerU16::new(vec![0u8; 256]), config).is_ok());
+    }
+}
+
+#[cfg(test)]
+mod serializer_max_depth {
+    use binja::{BinarySerialize, config::Config, error::Error, to_bytes_with_config};
+
+    #[derive(BinarySerialize)]
+    struct Node {
+        children: Vec<Node>,
+    }
+
+    fn chain(depth: usize) -> Node {
+        (0..depth).fold(Node { children: vec![] }, |node, _| Node {
+            children: vec![node],
+        })
+    }
+
+    #[test]
+    fn recursive_struct() {
+        // every level is a struct holding a vec
+        let config = Config::default().with_max_depth(8);
+        assert!(to_bytes_with_config(&chain(3), config).is_ok());
+        assert_eq!(
+            Err(Error::DepthLimitExceeded { max_depth: 8 }),
+            to_bytes_with_config(&chain(4), config)
+        );
+    }
+
+    #[test]
+    fn nested_vecs() {
+        let config = Config::default().with_max_depth(2);
+        assert!(to_bytes_with_config(&vec![vec![1u8]], config).is_ok());
+        assert_eq!(
+            Err(Error::DepthLimitExceeded { max_depth: 2 }),
+            to_bytes_with_config(&vec![vec![vec![1u8]]], config)
+        );
+    }
+}
+
+#[cfg(test)]
+mod serializer_size_attribute {
+    use binja::{BinarySerialize, error::Error, to_bytes};
+
+    #[derive(BinarySerialize)]
+    struct Packet {
+        len: u16,
+        #[binja(size = "len")]
+        payload: (u8, u16),
+        tail: u8,
+    }
+
+    #[test]
+    fn size_matches() {
+        let value = Packet {
+            len: 3,
+            payload: (1, 2),
+            tail: 3,
+        };
+        assert_eq!(
+            vec![0x03, 0x00, 0x01, 0x02, 0x00, 0x03],
+            to_bytes(&value).unwrap()
+        );
+    }
+
+    #[test]
+    fn size_mismatch() {
+        let value = Packet {
+            len: 4,
+            payload: (1, 2),
+            tail: 3,
+        };
+        assert_eq!(
+            Err(Error::InvalidLength {
+                expected: "4 bytes".to_owned(),
+                found: "3 bytes".to_owned()
+            }),
+            to_bytes(&value)
+        );
+    }
+}
+
+#[cfg(test)]
+mod serializer_reservation {
+    use binja::{
+        BinaryParse, BinarySerialize, BinarySerializer, SizeSink, SliceSink, config::Config,
+        error::Error, from_bytes, from_bytes_with_config, serialized_size, to_bytes,
+        to_bytes_with_config, to_slice,
+    };
+    #[cfg(feature = "std")]
+    use binja::{IoSink, Sink, to_writer};
+
+    #[test]
+    fn reserve_and_patch() {
+        let mut serializer = BinarySerializer::new(Config::default().with_big_endian());
+        let reservation = serializer.reserve(2).unwrap();
+        assert_eq!(0, reservation.position());
+        serializer.u8(0xaa).unwrap();
+        serializer.u8(0xbb).unwrap();
+        let len = (serializer.size() - reservation.position() - 2) as u16;
+        serializer.patch(reservation, &len).unwrap();
+        assert_eq!(vec![0x00, 0x02, 0xaa, 0xbb], serializer.output().to_vec());
+    }
+
+    #[test]
+    fn patch_size_mismatch() {
+        let mut serializer = BinarySerializer::new(Config::default());
+        let reservation = serializer.reserve(2).unwrap();
+        assert_eq!(
+            Err(Error::InvalidLength {
+                expected: "2 bytes".to_owned(),
+                found: "4 bytes".to_owned()
+            }),
+            serializer.patch(reservation, &1u32)
+        );
+    }
+
+    #[test]
+    fn large_reservation_in_slice() {
+        let mut buf = [0xffu8; 130];
+        let mut serializer = BinarySerializer::with_sink(
+            SliceSink::new(&mut buf),
+            Config::default().with_limit(130),
+        );
+        let reservation = serializer.reserve(129).unwrap();
+        serializer.u8(0x01).unwrap();
+        serializer.patch(reservation, &[0xaau8; 129]).unwrap();
+        assert_eq!(130, serializer.size());
+        assert_eq!([0xaa; 129], buf[..129]);
+        assert_eq!(0x01, buf[129]);
+
+        let mut serializer = BinarySerializer::new(Config::default().with_limit(100));
+        assert_eq!(
+            Some(Error::LimitExceeded {
+                limit: 100,
+                size: 129
+            }),
+            serializer.reserve(129).err()
+        );
+        assert_eq!(0, serializer.size());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn nested_reservations_on_writer() {
+        let mut serializer =
+            BinarySerializer::with_sink(IoSink::new(Vec::new()), Config::default());
+        serializer.u8(0x01).unwrap();
+        let outer = serializer.reserve(1).unwrap();
+        let inner = serializer.reserve(1).unwrap();
+        serializer.u8(0x02).unwrap();
+        // the outer reservation is still pending, so nothing was handed to the writer
+        serializer.patch(inner, &1u8).unwrap();
+        serializer.patch(outer, &2u8).unwrap();
+        assert_eq!(
+            &[0x01, 0x02, 0x01, 0x02],
+            serializer.into_sink().get_ref().as_slice()
+        );
+    }
+
+    #[derive(BinarySerialize, BinaryParse, PartialEq, Debug)]
+    struct Body {
+        name: String,
+        flags: u8,
+    }
+
+    #[derive(BinarySerialize, BinaryParse, PartialEq, Debug)]
+    struct Message {
+        kind: u8,
+        #[binja(len_of = "body")]
+        len: u32,
+        #[binja(size = "len")]
+        body: Body,
+        crc: u16,
+    }
+
+    fn message() -> Message {
+        Message {
+            kind: 7,
+            // ignored when serializing
+            len: 0,
+            body: Body {
+                name: "abc".to_owned(),
+                flags: 1,
+            },
+            crc: 0xbeef,
+        }
+    }
+
+    const BYTES: [u8; 17] = [
+        0x07, 0x08, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, b'a', b'b', b'c', 0x01, 0xef, 0xbe,
+        0x00, 0x00,
+    ];
+
+    #[test]
+    fn len_of() {
+        let bytes = to_bytes(&message()).unwrap();
+        assert_eq!(&BYTES[..15], &bytes[..]);
+
+        let (value, _) = from_bytes::<Message>(&bytes).unwrap();
+        assert_eq!(8, value.len);
+        assert_eq!(message().body, value.body);
+    }
+
+    #[test]
+    fn len_of_sinks() {
+        #[cfg(feature = "std")]
+        {
+            let mut writer = Vec::new();
+            to_writer(&message(), &mut writer).unwrap();
+            assert_eq!(&BYTES[..15], &writer[..]);
+        }
+
+        let mut buf = [0u8; 17];
+        assert_eq!(15, to_slice(&message(), &mut buf).unwrap());
+        assert_eq!(BYTES, buf);
+
+        assert_eq!(15, serialized_size(&message(), Config::default()).unwrap());
+        let mut sink = SizeSink::new();
+        message()
+            .binary_serialize(&mut BinarySerializer::with_sink(
+                &mut sink,
+                Config::default(),
+            ))
+            .unwrap();
+        assert_eq!(15, sink.size());
+    }
+
+    #[test]
+    fn len_of_fixed_under_varint() {
+        // the length keeps its fixed size, the body follows the configured encoding
+        let config = Config::default().with_varint_encoding();
+        let bytes = to_bytes_with_config(&message(), config).unwrap();
+        assert_eq!(
+            vec![
+                0x07, 0x08, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, b'a', b'b', b'c', 0x01
+            ],
+            bytes[..13].to_vec()
+        );
+
+        // and is read back with the same fixed size
+        let (value, remaining) = from_bytes_with_config::<Message>(&bytes, config).unwrap();
+        assert_eq!(0, remaining);
+        assert_eq!(8, value.len);
+        assert_eq!(message().body, value.body);
+        assert_eq!(message().crc, value.crc);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn unpatched_reservation_on_writer() {
+        let mut serializer =
+            BinarySerializer::with_sink(IoSink::new(Vec::new()), Config::default());
+        serializer.u8(0x01).unwrap();
+        let _reservation = serializer.reserve(1).unwrap();
+        serializer.u8(0x02).unwrap();
+        // the bytes after the reservation are still held back
+        let mut sink = serializer.into_sink();
+        assert!(matches!(sink.finish(), Err(Error::Message(_))));
+        assert_eq!(&[0x01], sink.get_ref().as_slice());
+    }
+
+    #[derive(BinarySerialize)]
+    struct Short {
+        #[binja(len_of = "data")]
+        len: u8,
+        data: Vec<u8>,
+    }
+
+    #[test]
+    fn len_of_overflow() {
+        let value = Short {
+            len: 0,
+            data: vec![0; 300],
+        };
+        assert_eq!(
+            Err(Error::Overflow {
+                value: "304".to_owned(),
+                max: "255".to_owned()
+            }),
+            to_bytes(&value)
+        );
+    }
+}
+
+#[cfg(test)]
+mod serializer_count {
+    use binja::{BinarySerialize, error::Error, to_bytes};
+
+    #[derive(BinarySerialize)]
+    struct Legacy {
+        num_items: u8,
+        flags: u8,
+        #[binja(count = "num_items")]
+        items: Vec<u16>,
+        #[binja(count = "*flags & 0x0f")]
+        extra: Vec<u8>,
+    }
+
+    #[test]
+    fn count_filled_in() {
+        let value = Legacy {
+            // filled in from `items`
+            num_items: 0,
+            flags: 0xf1,
+            items: vec![1, 2],
+            extra: vec![9],
+        };
+        assert_eq!(
+            vec![0x02, 0xf1, 0x01, 0x00, 0x02, 0x00, 0x09],
+            to_bytes(&value).unwrap()
+        );
+    }
+
+    #[test]
+    fn count_checked() {
+        let value = Legacy {
+            num_items: 0,
+            flags: 0xf2,
+            items: vec![],
+            extra: vec![9],
+        };
+        assert_eq!(
+            Err(Error::InvalidLength {
+                expected: "2 elements".to_owned(),
+                found: "1 elements".to_owned()
+            }),
+            to_bytes(&value)
+        );
+    }
+
+    #[derive(BinarySerialize)]
+    struct Small {
+        count: u8,
+        #[binja(count = "count")]
+        items: Vec<u8>,
+    }
+
+    #[test]
+    fn count_overflow() {
+        let value = Small {
+            count: 0,
+            items: vec![0; 256],
+        };
+        assert_eq!(
+            Err(Error::Overflow {
+                value: "256".to_owned(),
+                max: "255".to_owned()
+            }),
+            to_bytes(&value)
+        );
+    }
+}
+
+#[cfg(test)]
+mod serializer_conditional {
+    use binja::{BinarySerialize, error::Error, to_bytes};
+
+    #[derive(BinarySerialize)]
+    struct Frame {
+        flags: u8,
+        #[binja(if = "flags & 0x4 != 0")]
+        timestamp: Option<u32>,
+        #[binja(if = "flags & 0x1 != 0", varint)]
+        sequence: Option<u16>,
+        payload: u8,
+    }
+
+    #[test]
+    fn present_and_absent() {
+        let value = Frame {
+            flags: 0x05,
+            timestamp: Some(1),
+            sequence: Some(300),
+            payload: 0xff,
+        };
+        assert_eq!(
+            vec![0x05, 0x01, 0x00, 0x00, 0x00, 0xac, 0x02, 0xff],
+            to_bytes(&value).unwrap()
+        );
+
+        let value = Frame {
+            flags: 0x00,
+            timestamp: None,
+            sequence: None,
+            payload: 0xff,
+        };
+        assert_eq!(vec![0x00, 0xff], to_bytes(&value).unwrap());
+    }
+
+    #[test]
+    fn mismatch() {
+        let value = Frame {
+            flags: 0x04,
+            timestamp: None,
+            sequence: None,
+            payload: 0xff,
+        };
+        assert_eq!(
+            Err(Error::InvalidValue {
+                expected: "Some for `timestamp`, as its condition holds".to_owned(),
+                found: "None".to_owned()
+            }),
+            to_bytes(&value)
+        );
+
+        let value = Frame {
+            flags: 0x04,
+            timestamp: Some(1),
+            sequence: Some(2),
+            payload: 0xff,
+        };
+        assert_eq!(
+            Err(Error::InvalidValue {
+                expected: "None for `sequence`, as its condition doesn't hold".to_owned(),
+                found: "Some".to_owned()
+            }),
+            to_bytes(&value)
+        );
+    }
+}
+
+#[cfg(test)]
+mod serializer_magic {
+    use binja::{BinarySerialize, FixedEncodedSize, to_bytes};
+
+    #[derive(BinarySerialize)]
+    #[binja(magic = b"PK\x03\x04")]
+    struct Header {
+        #[binja(const = 0x0014)]
+        version: u16,
+        flags: u8,
+    }
+
+    #[test]
+    fn magic_and_const() {
+        let value = Header {
+            // the constant is written whatever the field holds
+            version: 0,
+            flags: 1,
+        };
+        assert_eq!(
+            vec![b'P', b'K', 0x03, 0x04, 0x14, 0x00, 0x01],
+            to_bytes(&value).unwrap()
+        );
+    }
+
+    #[test]
+    fn fixed_size() {
+        assert_eq!(7, Header::SIZE);
+    }
+}
+
+#[cfg(test)]
+mod serializer_padding {
+    use binja::{BinarySerialize, FixedEncodedSize, error::Error, to_bytes, to_slice};
+
+    #[derive(BinarySerialize)]
+    #[binja(pad_to = 12)]
+    struct Registers {
+        control: u8,
+        #[binja(align = 4)]
+        status: u32,
+        #[binja(pad_before = 1, pad_after = 1)]
+        mode: u8,
+    }
+
+    #[test]
+    fn written() {
+        let value = Registers {
+            control: 1,
+            status: 2,
+            mode: 3,
+        };
+        assert_eq!(
+            vec![
+                0x01, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00
+            ],
+            to_bytes(&value).unwrap()
+        );
+        assert_eq!(12, Registers::SIZE);
+    }
+
+    #[derive(BinarySerialize)]
+    #[binja(pad_to = 200, fill = 0xff)]
+    struct Sector {
+        id: u8,
+        #[binja(pad_before = 70)]
+        flags: u8,
+    }
+
+    #[test]
+    fn long_runs_into_slice() {
+        let mut buf = [0u8; 200];
+        assert_eq!(
+            200,
+            to_slice(&Sector { id: 1, flags: 2 }, &mut buf).unwrap()
+        );
+        assert_eq!(0x01, buf[0]);
+        assert!(buf[1..71].iter().all(|&b| b == 0xff));
+        assert_eq!(0x02, buf[71]);
+        assert!(buf[72..].iter().all(|&b| b == 0xff));
+    }
+
+    #[derive(BinarySerialize)]
+    #[binja(fill = 0xff)]
+    struct Filled {
+        #[binja(pad_after = 2)]
+        a: u8,
+        #[binja(pad_before = 1)]
+        b: u16,
+    }
+
+    #[test]
+    fn fill_byte() {
+        let value = Filled { a: 1, b: 2 };
+        assert_eq!(
+            vec![0x01, 0xff, 0xff, 0xff, 0x02, 0x00],
+            to_bytes(&value).unwrap()
+        );
+        assert_eq!(6, Filled::SIZE);
+    }
+
+    #[derive(BinarySerialize)]
+    #[binja(pad_to = 2)]
+    struct TooLong {
+        a: u16,
+        b: u8,
+    }
+
+    #[test]
+    fn pad_to_exceeded() {
+        assert_eq!(
+            Err(Error::InvalidLength {
+                expected: "at most 2 bytes".to_owned(),
+                found: "3 bytes".to_owned()
+            }),
+            to_bytes(&TooLong { a: 1, b: 2 })
+        );
+    }
+}
+
+#[cfg(test)]
+mod serializer_endianness_override {
+    use binja::{BinarySerialize, config::Config, to_bytes, to_bytes_with_config};
+
+    #[derive(BinarySerialize)]
+    #[binja(big_endian)]
+    struct NetworkHeader {
+        port: u16,
+        #[binja(little_endian)]
+        local: u16,
+    }
+
+    #[derive(BinarySerialize)]
+    struct Record {
+        #[binja(len_of = "header", big_endian)]
+        len: u16,
+        header: NetworkHeader,
+        #[binja(big_endian, varint)]
+        id: u32,
+        crc: u16,
+    }
+
+    #[test]
+    fn mixed() {
+        let value = Record {
+            len: 0,
+            header: NetworkHeader { port: 80, local: 2 },
+            id: 300,
+            crc: 4,
+        };
+        assert_eq!(
+            vec![0x00, 0x04, 0x00, 0x50, 0x02, 0x00, 0xac, 0x02, 0x04, 0x00],
+            to_bytes(&value).unwrap()
+        );
+    }
+
+    #[derive(BinarySerialize)]
+    #[binja(little_endian, repr = "u16")]
+    enum Message {
+        Data(u32),
+    }
+
+    #[test]
+    fn enum_override() {
+        let config = Config::default().with_big_endian();
+        // the tag follows the override as well, and the configuration is restored afterwards
+        assert_eq!(
+            vec![0x00, 0x00, 0x05, 0x00, 0x00, 0x00, 0x00, 0x01],
+            to_bytes_with_config(&(Message::Data(5), 1u16), config).unwrap()
+        );
+    }
+}
+
+#[cfg(test)]
+mod serializer_custom_codec {
+    use binja::{BinarySerialize, BinarySerializer, Sink, error::Result, to_bytes};
+
+    mod seconds {
+        use binja::{BinarySerialize, BinarySerializer, Sink, error::Result};
+        use core::time::Duration;
+
+        pub fn binary_serialize<S: Sink>(
+            value: &Duration,
+            serializer: &mut BinarySerializer<S>,
+        ) -> Result<()> {
+            (value.as_secs() as u32).binary_serialize(serializer)
+        }
+    }
+
+    fn as_byte<S: Sink>(value: &bool, serializer: &mut BinarySerializer<S>) -> Result<()> {
+        (if *value { 0xffu8 } else { 0x00u8 }).binary_serialize(serializer)
+    }
+
+    #[derive(BinarySerialize)]
+    struct Entry {
+        #[binja(with = "seconds")]
+        timeout: core::time::Duration,
+        #[binja(serialize_with = "as_byte")]
+        enabled: bool,
+        #[binja(serialize_with = "as_byte", big_endian)]
+        other: bool,
+    }
+
+    #[test]
+    fn fields() {
+        let value = Entry {
+            timeout: core::time::Duration::from_secs(3),
+            enabled: true,
+            other: false,
+        };
+        assert_eq!(
+            vec![0x03, 0x00, 0x00, 0x00, 0xff, 0x00],
+            to_bytes(&value).unwrap()
+        );
+    }
+
+    #[derive(BinarySerialize)]
+    enum Command {
+        Wait(#[binja(with = "seconds")] core::time::Duration),
+        Toggle {
+            #[binja(serialize_with = "as_byte")]
+            on: bool,
+        },
+    }
+
+    #[test]
+    fn enum_variant_fields() {
+        assert_eq!(
+            vec![0x00, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00],
+            to_bytes(&Command::Wait(core::time::Duration::from_secs(2))).unwrap()
+        );
+        assert_eq!(
+            vec![0x01, 0x00, 0x00, 0x00, 0xff],
+            to_bytes(&Command::Toggle { on: true }).unwrap()
+        );
+    }
+}
+
+#[cfg(test)]
+mod serializer_into {
+    use binja::{BinarySerialize, to_bytes};
+
+    #[derive(Clone, Copy)]
+    enum Kind {
+        File,
+        Directory,
+    }
+
+    impl From<Kind> for u8 {
+        fn from(kind: Kind) -> Self {
+            match kind {
+                Kind::File => 0,
+                Kind::Directory => 1,
+            }
+        }
+    }
+
+    #[derive(BinarySerialize)]
+    struct Entry {
+        version: u8,
+        #[binja(into = "u8")]
+        kind: Kind,
+        #[binja(into = "u8", if = "*version > 1")]
+        other: Option<Kind>,
+        #[binja(into = "u64", varint)]
+        size: u32,
+    }
+
+    #[test]
+    fn fields() {
+        let value = Entry {
+            version: 2,
+            kind: Kind::Directory,
+            other: Some(Kind::File),
+            size: 300,
+        };
+        assert_eq!(
+            vec![0x02, 0x01, 0x00, 0xac, 0x02],
+            to_bytes(&value).unwrap()
+        );
+    }
+
+    #[derive(BinarySerialize)]
+    enum Command {
+        Open(#[binja(into = "u8")] Kind),
+    }
+
+    #[test]
+    fn enum_variant_fields() {
+        assert_eq!(
+            vec![0x00, 0x00, 0x00, 0x00, 0x01],
+            to_bytes(&Command::Open(Kind::Directory)).unwrap()
+        );
+    }
+}
+
+#[cfg(test)]
+mod serializer_default_and_calc {
+    use binja::{BinarySerialize, FixedEncodedSize, to_bytes};
+
+    enum Origin {
+        Wire,
+    }
+
+    #[derive(BinarySerialize)]
+    struct Image {
+        width: u16,
+        height: u16,
+        #[binja(calc = "u32::from(*width) * u32::from(*height)")]
+        pixels: u32,
+        #[binja(skip, default = "Origin::Wire")]
+        origin: Origin,
+        depth: u8,
+    }
+
+    #[test]
+    fn fields() {
+        let value = Image {
+            width: 2,
+            height: 3,
+            pixels: 6,
+            origin: Origin::Wire,
+            depth: 8,
+        };
+        // computed and skipped fields are never written
+        assert_eq!(
+            vec![0x02, 0x00, 0x03, 0x00, 0x08],
+            to_bytes(&value).unwrap()
+        );
+        assert_eq!(5, Image::SIZE);
+    }
+}
+
+#[cfg(test)]
+mod serializer_variant_tags {
+    use binja::{BinarySerialize, to_bytes};
+
+    mod opcodes {
+        pub const PUSH: u8 = 0x20;
+        pub const PNG: [u8; 4] = *b"\x89PNG";
+    }
+
+    #[derive(BinarySerialize)]
+    #[binja(repr = "u8")]
+    enum Instruction {
+        #[binja(tag = 0x10)]
+        Nop,
+        // continues from the discriminant, not the tag
+        Halt,
+        #[binja(tag = opcodes::PUSH)]
+        Push(u16),
+    }
+
+    #[derive(BinarySerialize)]
+    enum Image {
+        #[binja(tag = opcodes::PNG)]
+        Png { width: u16 },
+        #[binja(tag = b"GIF8")]
+        Gif(u8),
+    }
+
+    #[test]
+    fn integer_tags() {
+        assert_eq!(vec![0x10], to_bytes(&Instruction::Nop).unwrap());
+        assert_eq!(vec![0x01], to_bytes(&Instruction::Halt).unwrap());
+        assert_eq!(
+            vec![0x20, 0x07, 0x00],
+            to_bytes(&Instruction::Push(7)).unwrap()
+        );
+    }
+
+    #[test]
+    fn byte_string_tags() {
+        assert_eq!(
+            b"\x89PNG\x05\x00".to_vec(),
+            to_bytes(&Image::Png { width: 5 }).unwrap()
+        );
+        assert_eq!(b"GIF8\x03".to_vec(), to_bytes(&Image::Gif(3)).unwrap());
+    }
+}